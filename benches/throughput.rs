@@ -0,0 +1,65 @@
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use pngme::chunk::Chunk;
+use pngme::chunk_type::ChunkType;
+use pngme::png::Png;
+
+const SIZES: [(&str, usize); 3] = [("1KB", 1024), ("1MB", 1024 * 1024), ("16MB", 16 * 1024 * 1024)];
+
+// Deterministic, non-uniform payload so nothing benefits from all-zero data
+fn payload(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i.wrapping_mul(31) ^ (i >> 7)) as u8).collect()
+}
+
+fn chunk(size: usize) -> Chunk {
+    Chunk::new(ChunkType::from_str("ruSt").unwrap(), payload(size))
+}
+
+fn png(size: usize) -> Png {
+    Png::from_chunks(vec![
+        Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+        Chunk::new(ChunkType::from_str("IDAT").unwrap(), payload(size)),
+        Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+    ])
+}
+
+fn bench_chunk_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunk_try_from");
+    for &(name, size) in SIZES.iter() {
+        let bytes = chunk(size).as_bytes();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            b.iter(|| Chunk::try_from(bytes.as_ref()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_chunk_serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunk_as_bytes");
+    for &(name, size) in SIZES.iter() {
+        let chunk = chunk(size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &chunk, |b, chunk| {
+            b.iter(|| chunk.as_bytes())
+        });
+    }
+    group.finish();
+}
+
+fn bench_png_round_trip(c: &mut Criterion) {
+    let mut group = c.benchmark_group("png_round_trip");
+    for &(name, size) in SIZES.iter() {
+        let bytes = png(size).as_bytes();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            b.iter(|| Png::try_from(bytes.as_ref()).unwrap().as_bytes())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_chunk_parse, bench_chunk_serialize, bench_png_round_trip);
+criterion_main!(benches);
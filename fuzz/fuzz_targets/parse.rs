@@ -0,0 +1,24 @@
+#![no_main]
+
+use std::convert::TryFrom;
+
+use libfuzzer_sys::fuzz_target;
+use pngme::chunk::{Chunk, ParseOptions};
+use pngme::png::Png;
+
+// Parsing arbitrary bytes must never panic, only return `Err`.
+// Anything that does parse must serialize back to a chunk that parses again.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(chunk) = Chunk::try_from(data) {
+        let bytes = chunk.as_bytes();
+        assert_eq!(bytes, data[..bytes.len()]);
+        assert!(Chunk::try_from(bytes.as_ref()).is_ok());
+    }
+
+    if let Ok(png) = Png::try_from(data) {
+        assert_eq!(png.as_bytes(), data);
+    }
+
+    let _ = Png::parse(data, &ParseOptions::lenient());
+    let _ = Png::parse(data, &ParseOptions::strict());
+});
@@ -0,0 +1,297 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use pngme::in_memory::DEFAULT_MAX_MESSAGE_SIZE;
+use pngme::text_encoding::TextEncoding;
+
+#[cfg(feature = "color")]
+use crate::style::ColorChoice;
+
+#[derive(Debug, Parser)]
+#[command(name = "pngme", about = "Hide secret messages in PNG files")]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+    #[command(subcommand)]
+    pub command: PngMeArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct GlobalArgs {
+    /// Print results and errors as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Color the output of print, validate and list-types
+    #[cfg(feature = "color")]
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PngMeArgs {
+    /// Encode a message into a PNG file
+    Encode(EncodeArgs),
+    /// Encode the same message into many PNG files
+    BatchEncode(BatchEncodeArgs),
+    /// Decode a message stored in a PNG file
+    Decode(DecodeArgs),
+    /// Remove a chunk from a PNG file
+    Remove(RemoveArgs),
+    /// Print every chunk of a PNG file
+    Print(PrintArgs),
+    /// Print a hex dump of the data of one chunk
+    Dump(DumpArgs),
+    /// Describe the chunks of a PNG, or a single chunk with --raw
+    ChunkInfo(ChunkInfoArgs),
+    /// Check the structure and every CRC of a PNG file
+    Validate(ValidateArgs),
+    /// Remove every ancillary chunk from a PNG file
+    Strip(StripArgs),
+    /// Change the type of every chunk of one type, keeping data and position
+    RenameType(RenameTypeArgs),
+    /// Exit successfully if a file starts with the PNG signature
+    IsPng(IsPngArgs),
+    /// Add a tEXt chunk with a keyword and text to a PNG file
+    SetText(SetTextArgs),
+    /// Generate a private ancillary chunk type from a word
+    GenType(GenTypeArgs),
+    /// List the chunk types defined by the PNG spec
+    ListTypes,
+}
+
+#[derive(Debug, Args)]
+pub struct ParseArgs {
+    /// Enforce the PNG signature, chunk type rules and structure while reading
+    #[arg(long)]
+    pub strict: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct EncodeArgs {
+    /// PNG file to encode the message into
+    pub file_path: PathBuf,
+    /// Chunk type to store the message under, e.g. ruSt
+    #[arg(required_unless_present = "interactive")]
+    pub chunk_type: Option<String>,
+    /// Message to store
+    #[arg(required_unless_present = "interactive")]
+    pub message: Option<String>,
+    /// Prompt for the chunk type and message when they are not given
+    #[arg(short, long)]
+    pub interactive: bool,
+    /// Recase the chunk type as a private, ancillary, safe-to-copy type
+    #[arg(long)]
+    pub normalize: bool,
+    /// Encoding to store the message in: utf-8, latin1 or windows-1252
+    #[arg(long, default_value = "utf-8")]
+    pub encoding: TextEncoding,
+    /// The message is a hex string to store as raw bytes
+    #[arg(long, conflicts_with = "encoding")]
+    pub hex: bool,
+    /// The message is a base64 string to store as raw bytes
+    #[cfg(feature = "base64")]
+    #[arg(long, conflicts_with_all = ["encoding", "hex"])]
+    pub base64: bool,
+    /// Write the result here instead of overwriting the input file
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Copy the input file to <FILE_PATH>.bak before writing
+    #[arg(long)]
+    pub backup: bool,
+    /// Validate the PNG structure before writing and refuse to write an invalid file
+    #[arg(long)]
+    pub validate: bool,
+    /// Write the file even if validation fails
+    #[arg(long, requires = "validate")]
+    pub force: bool,
+    /// Place the new chunk directly after the last chunk of this type
+    #[arg(long, value_name = "CHUNK_TYPE")]
+    pub after_type: Option<String>,
+    /// Place the new chunk at this chunk index
+    #[arg(long, value_name = "INDEX", conflicts_with = "after_type")]
+    pub insert_at: Option<usize>,
+    /// Refuse to store messages larger than this many bytes
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_MESSAGE_SIZE)]
+    pub max_message_size: usize,
+    /// Refuse to write the file if it would be larger than this many bytes
+    #[arg(long, value_name = "BYTES")]
+    pub max_output_size: Option<usize>,
+    /// Also add a pnGm chunk recording the pngme version and how the message is stored
+    #[arg(long)]
+    pub version_chunk: bool,
+    /// Print the chunk count and file size before and after to stderr
+    #[arg(long)]
+    pub summary: bool,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct BatchEncodeArgs {
+    /// Chunk type to store the message under, e.g. ruSt
+    pub chunk_type: String,
+    /// Message to store
+    pub message: String,
+    /// PNG files to encode the message into, each is overwritten in place
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    /// Encoding to store the message in: utf-8, latin1 or windows-1252
+    #[arg(long, default_value = "utf-8")]
+    pub encoding: TextEncoding,
+    /// Number of files to encode at once, defaults to one per CPU
+    #[cfg(feature = "parallel")]
+    #[arg(long, value_name = "N")]
+    pub threads: Option<usize>,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct DecodeArgs {
+    /// PNG file to decode the message from
+    pub file_path: PathBuf,
+    /// Chunk type the message is stored under
+    pub chunk_type: String,
+    /// Encoding the message is stored in: utf-8, latin1 or windows-1252
+    #[arg(long, default_value = "utf-8")]
+    pub encoding: TextEncoding,
+    /// Print the message as a hex string instead of text
+    #[arg(long, conflicts_with = "encoding")]
+    pub hex: bool,
+    /// Read chunks even if their CRC does not match their contents
+    #[arg(long, conflicts_with = "strict")]
+    pub ignore_crc: bool,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoveArgs {
+    /// PNG file to remove the chunk from
+    pub file_path: PathBuf,
+    /// Chunk type of the chunk to remove
+    pub chunk_type: String,
+    /// Report how many chunks were removed and how many of the type remain
+    #[arg(long)]
+    pub count: bool,
+    /// Remove every chunk of the type instead of only the first
+    #[arg(long)]
+    pub all: bool,
+    /// Allow removing critical chunks such as IHDR or IDAT
+    #[arg(long)]
+    pub force: bool,
+    /// Print the chunk count and file size before and after to stderr
+    #[arg(long)]
+    pub summary: bool,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct PrintArgs {
+    /// PNG file to print
+    pub file_path: PathBuf,
+    /// Print one `type | length | crc` row per chunk
+    #[arg(long)]
+    pub compact: bool,
+    /// Show the byte offset of each chunk within the file
+    #[arg(long)]
+    pub offset: bool,
+    /// Print only the chunk types, one per line
+    #[arg(long, conflicts_with_all = ["compact", "offset"])]
+    pub type_only: bool,
+    /// With --type-only, print the types on one comma-separated line
+    #[arg(long, requires = "type_only")]
+    pub inline: bool,
+    /// Show the Shannon entropy of each chunk's data, in bits per byte
+    #[arg(long, conflicts_with = "type_only")]
+    pub entropy: bool,
+    /// Read chunks even if their CRC does not match their contents
+    #[arg(long, conflicts_with = "strict")]
+    pub ignore_crc: bool,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct DumpArgs {
+    /// PNG file to read
+    pub file_path: PathBuf,
+    /// Chunk type of the chunk to dump
+    #[arg(long = "type", value_name = "CHUNK_TYPE")]
+    pub chunk_type: String,
+    /// Which chunk of that type to dump when there are several, counting from 0
+    #[arg(long, default_value_t = 0)]
+    pub index: usize,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// PNG file to validate
+    pub file_path: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct StripArgs {
+    /// PNG file to strip
+    pub file_path: PathBuf,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ChunkInfoArgs {
+    /// File to read, or standard input if omitted
+    pub file_path: Option<PathBuf>,
+    /// Read a single serialized chunk (length, type, data, crc) without a PNG signature
+    #[arg(long)]
+    pub raw: bool,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct RenameTypeArgs {
+    /// PNG file to rename chunks in
+    pub file_path: PathBuf,
+    /// Current chunk type, e.g. ruSt
+    #[arg(long)]
+    pub from: String,
+    /// New chunk type, e.g. nOte
+    #[arg(long)]
+    pub to: String,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct SetTextArgs {
+    /// PNG file to add the text to
+    pub file_path: PathBuf,
+    /// Keyword of the text chunk, e.g. Title or Comment
+    pub keyword: String,
+    /// Text to store, written as Latin-1
+    pub text: String,
+    /// Replace the first tEXt chunk with the same keyword instead of adding another
+    #[arg(long)]
+    pub replace: bool,
+    #[command(flatten)]
+    pub parse: ParseArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct IsPngArgs {
+    /// File to check
+    pub file_path: PathBuf,
+    /// Print whether the file is a PNG instead of only setting the exit code
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct GenTypeArgs {
+    /// Word to build the chunk type from, only its first four letters are used
+    pub name: String,
+}
@@ -2,16 +2,29 @@
 use crate::{Error, Result};
 use std::{
     convert::{TryFrom, TryInto},
-    fmt::Display
+    fmt::{self, Display},
+    io::{self, Read, Write},
 };
-use crate::chunk_type::ChunkType;
+use crate::chunk_type::{ChunkType, ChunkTypeError};
+use crate::text_encoding::TextEncoding;
 
 #[derive(Debug)]
 pub struct Chunk {
     chunk_type: ChunkType,
     message_bytes: Vec<u8>,
+    // CRC read from the input, kept so it can be written back unchanged
+    stored_crc: Option<u32>,
 }
 
+// Chunks are equal when their type and data are, wherever their CRC came from
+impl PartialEq for Chunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.chunk_type == other.chunk_type && self.message_bytes == other.message_bytes
+    }
+}
+
+impl Eq for Chunk {}
+
 impl Chunk {
     pub const DATA_LENGTH_BYTES: usize = 4;
     pub const CHUNK_TYPE_BYTES: usize = 4;
@@ -20,30 +33,146 @@ impl Chunk {
     pub const MINIUMUM_LENGTH: usize =
         Chunk::DATA_LENGTH_BYTES + Chunk::CHUNK_TYPE_BYTES + Chunk::CRC_BYTES;
 
+    /*
+        Creates a Chunk that takes ownership of `message_bytes` without copying it
+    */
     pub fn new(chunk_type: ChunkType, message_bytes: Vec<u8>) -> Self {
-        Self { chunk_type, message_bytes }
+        Self { chunk_type, message_bytes, stored_crc: None }
+    }
+
+    /*
+        Like `new`, but only accepts chunk types that are safe for storing
+        arbitrary data: every byte a letter, a valid (uppercase) reserved bit,
+        and ancillary (lowercase first letter), so viewers may ignore the chunk.
+        The public and safe-to-copy bits are not checked.
+    */
+    pub fn new_checked(chunk_type: ChunkType, message_bytes: Vec<u8>) -> Result<Self> {
+        chunk_type.validate_with_reason().map_err(ChunkError::from)?;
+        if chunk_type.is_critical() {
+            return Err(Box::from(ChunkError::CriticalChunkType(chunk_type)));
+        }
+        Ok(Self::new(chunk_type, message_bytes))
+    }
+
+    /*
+        Creates an empty Chunk with room for `capacity` message bytes, to be
+        filled with `extend_data` without reallocating
+    */
+    pub fn with_capacity(chunk_type: ChunkType, capacity: usize) -> Self {
+        Self::new(chunk_type, Vec::with_capacity(capacity))
+    }
+
+    /*
+        Replaces the message bytes of this Chunk, returning the old ones.
+        The CRC always reflects the current data, and any stored CRC is dropped.
+    */
+    pub fn set_data(&mut self, message_bytes: Vec<u8>) -> Vec<u8> {
+        self.stored_crc = None;
+        std::mem::replace(&mut self.message_bytes, message_bytes)
+    }
+
+    /*
+        Changes the type of this Chunk, returning the old one. The data is
+        kept as is, and any stored CRC is dropped since it covers the type.
+    */
+    pub fn set_chunk_type(&mut self, chunk_type: ChunkType) -> ChunkType {
+        self.stored_crc = None;
+        std::mem::replace(&mut self.chunk_type, chunk_type)
+    }
+
+    /*
+        Consuming version of `set_chunk_type` that passes the current type
+        through `f`, e.g. to flip its safe-to-copy bit. The CRC follows the new type.
+    */
+    pub fn map_type<F: FnOnce(ChunkType) -> ChunkType>(mut self, f: F) -> Chunk {
+        let chunk_type = f(self.chunk_type);
+        self.set_chunk_type(chunk_type);
+        self
+    }
+
+    /*
+        Appends `bytes` to the message bytes of this Chunk, dropping any stored CRC
+    */
+    pub fn extend_data(&mut self, bytes: &[u8]) {
+        self.stored_crc = None;
+        self.message_bytes.extend_from_slice(bytes);
     }
 
     pub fn length(&self) -> usize {
         self.message_bytes.len()
     }
 
+    /*
+        Returns the number of bytes `as_bytes` produces for this Chunk:
+        the length, type and CRC fields plus the message bytes
+    */
+    pub fn serialized_len(&self) -> usize {
+        Chunk::MINIUMUM_LENGTH + self.length()
+    }
+
     pub fn chunk_type(&self) -> &ChunkType {
         &self.chunk_type
     }
 
+    /*
+        Computes the CRC over `crc_input` incrementally, without copying the data
+    */
     pub fn crc(&self) -> u32 {
-        let bytes: Vec<u8> = self.chunk_type
+        Chunk::compute_crc(&self.chunk_type, &self.message_bytes)
+    }
+
+    /*
+        Computes the CRC a chunk with `chunk_type` and `data` would have,
+        without building one, e.g. to check what renaming a chunk would write
+    */
+    pub fn compute_crc(chunk_type: &ChunkType, data: &[u8]) -> u32 {
+        use crc::crc32::{update, IEEE_TABLE};
+        let crc = update(0, &IEEE_TABLE, chunk_type.bytes_ref());
+        update(crc, &IEEE_TABLE, data)
+    }
+
+    /*
+        Returns the CRC as the 4 big-endian bytes that are written to disk
+    */
+    pub fn crc_bytes(&self) -> [u8; 4] {
+        self.crc().to_be_bytes()
+    }
+
+    /*
+        Returns the CRC this Chunk was parsed with, which may not match `crc`
+        if CRC checks were skipped. Chunks that were built or modified in
+        memory have none.
+    */
+    pub fn stored_crc(&self) -> Option<u32> {
+        self.stored_crc
+    }
+
+    /*
+        Returns the CRC to write for `mode`: the stored one when preserving,
+        falling back to the computed one if there is nothing stored
+    */
+    pub fn crc_for(&self, mode: CrcMode) -> u32 {
+        match (mode, self.stored_crc) {
+            (CrcMode::Preserve, Some(stored)) => stored,
+            _ => self.crc(),
+        }
+    }
+
+    /*
+        Returns the bytes the CRC is computed over: the chunk type followed by
+        the message bytes. Per the PNG spec the length field is not included.
+    */
+    pub fn crc_input(&self) -> Vec<u8> {
+        self.chunk_type
             .bytes()
             .iter()
             .chain(self.message_bytes.iter())
             .copied()
-            .collect();
-        crc::crc32::checksum_ieee(&bytes)
+            .collect()
     }
 
     pub fn data(&self) -> &[u8] {
-        &self.message_bytes.as_slice()
+        self.message_bytes.as_slice()
     }
 
     /*
@@ -54,15 +183,62 @@ impl Chunk {
         - The CRC of the Chunk (4 bytes)
     */
     pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.serialized_len());
+        bytes.extend(self.iter_bytes());
+        bytes
+    }
+
+    /*
+        Serializes the Chunk like `as_bytes` but with every CRC bit inverted,
+        so the result always fails CRC verification. For negative tests.
+    */
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn with_corrupt_crc(self) -> Vec<u8> {
+        let crc = !self.crc();
+        let mut bytes = self.as_bytes();
+        let crc_start = bytes.len() - Chunk::CRC_BYTES;
+        bytes[crc_start..].copy_from_slice(&crc.to_be_bytes());
+        bytes
+    }
+
+    /*
+        Lazily yields the same bytes as `as_bytes`. The message bytes are
+        borrowed from the Chunk, so nothing is copied or allocated.
+    */
+    pub fn iter_bytes(&self) -> impl Iterator<Item = u8> + '_ {
         let data_length = self.message_bytes.len() as u32;
-        data_length
-            .to_be_bytes()
-            .iter()
-            .chain(self.chunk_type().bytes().iter())
-            .chain(self.data().iter())
-            .chain(self.crc().to_be_bytes().iter())
-            .copied()
-            .collect()
+        IntoIterator::into_iter(data_length.to_be_bytes())
+            .chain(self.chunk_type.bytes())
+            .chain(self.message_bytes.iter().copied())
+            .chain(self.crc_bytes())
+    }
+
+    /*
+        Writes the bytes of `as_bytes` to `writer` without building them in
+        memory first. Returns the number of bytes written.
+    */
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<usize> {
+        self.write_to_with(writer, CrcMode::Recompute)
+    }
+
+    /*
+        Like `write_to`, but writes the CRC chosen by `mode`
+    */
+    pub fn write_to_with<W: Write>(&self, writer: &mut W, mode: CrcMode) -> Result<usize> {
+        writer.write_all(&(self.message_bytes.len() as u32).to_be_bytes())?;
+        writer.write_all(self.chunk_type.bytes_ref())?;
+        writer.write_all(&self.message_bytes)?;
+        writer.write_all(&self.crc_for(mode).to_be_bytes())?;
+        Ok(self.serialized_len())
+    }
+
+    /*
+        Yields the message bytes as borrowed slices of at most `size` bytes,
+        all full-sized except possibly the last. A `size` of 0 yields nothing.
+    */
+    pub fn data_chunks(&self, size: usize) -> impl Iterator<Item = &[u8]> {
+        let data = if size == 0 { &[][..] } else { &self.message_bytes[..] };
+        data.chunks(size.max(1))
     }
 
     /*
@@ -73,12 +249,275 @@ impl Chunk {
         Ok(data_string.to_string())
     }
 
-}
+    /*
+        Returns a fixed-width `type | length | crc` row for tabular listings
+    */
+    pub fn summary_line(&self) -> String {
+        self.summary_line_with(self.chunk_type())
+    }
 
-impl TryFrom<&[u8]> for Chunk {
-    type Error = Error;
+    /*
+        Same as `summary_line`, showing `chunk_type` in place of the chunk
+        type, e.g. a colored rendering of it
+    */
+    pub fn summary_line_with(&self, chunk_type: &dyn Display) -> String {
+        format!("{} | {:>10} | {:>10}", chunk_type, self.length(), self.crc())
+    }
+
+    /*
+        Writes the Display layout of the chunk, showing `chunk_type` in place
+        of the chunk type, `data` in place of the data length when given, and
+        one `  name: value` line per entry of `extras` right before the Crc line
+    */
+    pub fn write_with<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        chunk_type: &dyn Display,
+        data: Option<&dyn Display>,
+        extras: &[(&str, String)],
+    ) -> fmt::Result {
+        writeln!(out, "Chunk {{",)?;
+        writeln!(out, "  Length: {}", self.length())?;
+        writeln!(out, "  Type: {}", chunk_type)?;
+        match data {
+            Some(data) => writeln!(out, "  Data: {}", data)?,
+            None => writeln!(out, "  Data: {} bytes", self.data().len())?,
+        }
+        for (name, value) in extras {
+            writeln!(out, "  {}: {}", name, value)?;
+        }
+        writeln!(out, "  Crc: {}", self.crc())?;
+        writeln!(out, "}}",)?;
+        Ok(())
+    }
+
+    /*
+        Returns the Shannon entropy of the message bytes in bits per byte,
+        from 0.0 (empty, or one repeated byte) to 8.0 (every byte value
+        equally often). Encrypted or compressed data sits close to 8.0,
+        plain text usually well below it.
+    */
+    pub fn data_entropy(&self) -> f64 {
+        let data = self.data();
+        if data.is_empty() {
+            return 0.0;
+        }
+        let mut counts = [0usize; 256];
+        for &byte in data {
+            counts[byte as usize] += 1;
+        }
+        let total = data.len() as f64;
+        counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total;
+                p * (1.0 / p).log2()
+            })
+            .sum()
+    }
+
+    /*
+        Returns the message bytes encoded as standard, padded base64
+    */
+    #[cfg(feature = "base64")]
+    pub fn data_base64(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(&self.message_bytes)
+    }
+
+    /*
+        Returns the keyword of a text chunk: the bytes before the first null,
+        if there is one and they are valid utf-8. The data is not copied.
+    */
+    pub fn text_keyword(&self) -> Option<&str> {
+        let end = self.message_bytes.iter().position(|&b| b == 0)?;
+        std::str::from_utf8(&self.message_bytes[..end]).ok()
+    }
+
+    /*
+        Builds a `tEXt` chunk holding `keyword`, a null separator and `text`,
+        both stored as Latin-1. The keyword must follow the spec: 1-79
+        printable characters with no leading, trailing or consecutive spaces.
+        The text may not contain a null character.
+    */
+    pub fn new_text(keyword: &str, text: &str) -> Result<Self> {
+        let keyword = TextEncoding::Latin1.encode(keyword)?;
+        Chunk::new_text_bytes(&keyword, &TextEncoding::Latin1.encode(text)?)
+    }
+
+    /*
+        Like `new_text`, but takes the keyword and value as raw Latin-1 bytes,
+        so values don't have to be valid utf-8. The keyword is still checked
+        against the spec rules and the value may not contain a null byte.
+    */
+    pub fn new_text_bytes(keyword: &[u8], value: &[u8]) -> Result<Self> {
+        validate_keyword(keyword).map_err(|reason| ChunkError::InvalidKeyword(reason.to_string()))?;
+        if value.contains(&0) {
+            return Err(Box::from(ChunkError::InvalidTextChunk("tEXt text may not contain a null byte".to_string())));
+        }
+        let mut data = Vec::with_capacity(keyword.len() + 1 + value.len());
+        data.extend_from_slice(keyword);
+        data.push(0);
+        data.extend_from_slice(value);
+
+        Ok(Chunk::new(ChunkType::try_from(*b"tEXt")?, data))
+    }
+
+    /*
+        Returns the keyword and text of a `tEXt` chunk decoded from Latin-1,
+        or `None` if this isn't a `tEXt` chunk or has no null separator.
+    */
+    pub fn as_text(&self) -> Option<(String, String)> {
+        if self.chunk_type.bytes() != *b"tEXt" {
+            return None;
+        }
+        let end = self.message_bytes.iter().position(|&b| b == 0)?;
+        let keyword = TextEncoding::Latin1.decode(&self.message_bytes[..end]).ok()?;
+        let text = TextEncoding::Latin1.decode(&self.message_bytes[end + 1..]).ok()?;
+        Some((keyword, text))
+    }
+
+    /*
+        Consuming version of `as_text`: splits the data of a `tEXt` chunk into
+        its keyword and text. Only the short keyword is copied out; plain ASCII
+        text stays in the chunk's own buffer. Errors if this isn't a `tEXt`
+        chunk or has no null separator.
+    */
+    pub fn try_into_text(self) -> Result<(String, String)> {
+        if self.chunk_type.bytes() != *b"tEXt" {
+            return Err(Box::from(ChunkError::NotTextChunk(self.chunk_type)));
+        }
+        let end = self.message_bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| ChunkError::InvalidTextChunk("missing null separator after the keyword".to_string()))?;
+        let mut text = self.message_bytes;
+        let mut keyword: Vec<u8> = text.drain(..=end).collect();
+        keyword.pop();
+        Ok((latin1_into_string(keyword)?, latin1_into_string(text)?))
+    }
+
+    /*
+        Builds a `zTXt` chunk holding `keyword`, a null separator, compression
+        method 0 and `text` compressed with zlib. Both are stored as Latin-1
+        and the keyword follows the same rules as in `new_text`.
+    */
+    #[cfg(feature = "compress")]
+    pub fn new_ztxt(keyword: &str, text: &str) -> Result<Self> {
+        use flate2::{write::ZlibEncoder, Compression};
+
+        let keyword = TextEncoding::Latin1.encode(keyword)?;
+        validate_keyword(&keyword).map_err(|reason| ChunkError::InvalidKeyword(reason.to_string()))?;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&TextEncoding::Latin1.encode(text)?)?;
+        let compressed = encoder.finish()?;
+
+        let mut data = Vec::with_capacity(keyword.len() + 2 + compressed.len());
+        data.extend_from_slice(&keyword);
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&compressed);
+        Ok(Chunk::new(ChunkType::try_from(*b"zTXt")?, data))
+    }
+
+    /*
+        Returns the keyword and decompressed text of a `zTXt` chunk decoded
+        from Latin-1. Errors with the reason from `validate_text` if this isn't
+        a well-formed `zTXt` chunk, and if the text fails to decompress or
+        would decompress to more than `max_text_length` bytes.
+    */
+    #[cfg(feature = "compress")]
+    pub fn as_ztxt(&self, max_text_length: usize) -> Result<(String, String)> {
+        use flate2::read::ZlibDecoder;
+
+        if self.chunk_type.bytes() != *b"zTXt" {
+            return Err(Box::from(ChunkError::NotTextChunk(self.chunk_type)));
+        }
+        self.validate_text()?;
+        // validate_text guarantees a null and a compression method byte
+        let end = self.message_bytes.iter().position(|&b| b == 0).unwrap_or_default();
+        // read one byte past the limit to tell text at the limit from text over it
+        let mut text = Vec::new();
+        ZlibDecoder::new(&self.message_bytes[end + 2..])
+            .take(max_text_length as u64 + 1)
+            .read_to_end(&mut text)
+            .map_err(|e| ChunkError::InvalidCompressedData(e.to_string()))?;
+        if text.len() > max_text_length {
+            return Err(Box::from(ChunkError::InvalidCompressedData(format!(
+                "text decompresses to more than {} bytes",
+                max_text_length
+            ))));
+        }
+
+        let keyword = TextEncoding::Latin1.decode(&self.message_bytes[..end])?;
+        Ok((keyword, TextEncoding::Latin1.decode(&text)?))
+    }
+
+    /*
+        Checks that a `tEXt`, `zTXt` or `iTXt` chunk is laid out as the spec
+        requires: a valid keyword, the right null separators and, for `iTXt`,
+        utf-8 in the translated keyword and uncompressed text. Errors for any
+        other chunk type.
+    */
+    pub fn validate_text(&self) -> Result<()> {
+        let chunk_type = self.chunk_type.bytes();
+        if !matches!(&chunk_type, b"tEXt" | b"zTXt" | b"iTXt") {
+            return Err(Box::from(ChunkError::NotTextChunk(self.chunk_type)));
+        }
+        let end = match self.message_bytes.iter().position(|&b| b == 0) {
+            Some(end) => end,
+            None => return invalid_text("missing null separator after the keyword".to_string()),
+        };
+        validate_keyword(&self.message_bytes[..end]).map_err(|reason| ChunkError::InvalidKeyword(reason.to_string()))?;
+        let rest = &self.message_bytes[end + 1..];
+
+        match &chunk_type {
+            b"tEXt" if rest.contains(&0) => invalid_text("tEXt text may not contain a null byte".to_string()),
+            b"zTXt" => match rest.first() {
+                None => invalid_text("zTXt is missing its compression method".to_string()),
+                Some(0) => Ok(()),
+                Some(method) => invalid_text(format!("unknown compression method {}", method)),
+            },
+            b"iTXt" => {
+                let (flag, method, rest) = match rest {
+                    [flag, method, rest @ ..] => (*flag, *method, rest),
+                    _ => return invalid_text("iTXt is missing its compression flag and method".to_string()),
+                };
+                if flag > 1 {
+                    return invalid_text(format!("compression flag must be 0 or 1, got {}", flag));
+                }
+                if flag == 1 && method != 0 {
+                    return invalid_text(format!("unknown compression method {}", method));
+                }
+                let mut fields = rest.splitn(3, |&b| b == 0);
+                let (language, translated, text) = match (fields.next(), fields.next(), fields.next()) {
+                    (Some(language), Some(translated), Some(text)) => (language, translated, text),
+                    _ => return invalid_text("iTXt needs a null after the language tag and the translated keyword".to_string()),
+                };
+                if !language.is_ascii() {
+                    return invalid_text("iTXt language tag must be ASCII".to_string());
+                }
+                if std::str::from_utf8(translated).is_err() {
+                    return invalid_text("iTXt translated keyword is not valid utf-8".to_string());
+                }
+                if flag == 0 && (text.contains(&0) || std::str::from_utf8(text).is_err()) {
+                    return invalid_text("iTXt text must be utf-8 without null bytes".to_string());
+                }
+                Ok(())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /*
+        Parses a Chunk from the start of `bytes`, ignoring anything after it.
+        Strict parsing requires a fully valid chunk type; lenient parsing only
+        requires the type bytes to be ASCII letters.
+    */
+    pub fn parse(bytes: &[u8], options: &ParseOptions) -> Result<Self> {
+        #[cfg(feature = "trace")]
+        let _span = tracing::trace_span!("chunk_parse", len = bytes.len(), strict = options.strict).entered();
 
-    fn try_from(bytes: &[u8]) -> Result<Self> {
         // chunk bytes must be 12 bytes long at a minimum
         if bytes.len() < Chunk::MINIUMUM_LENGTH {
             return Err(Box::from(ChunkError::InputTooSmall))
@@ -91,23 +530,92 @@ impl TryFrom<&[u8]> for Chunk {
         let chunk_type_bytes: [u8; 4] = chunk_type_bytes.try_into()?;
         let chunk_type: ChunkType = ChunkType::try_from(chunk_type_bytes)?;
         // validate chunk type
-        if !chunk_type.is_valid() {
-            return Err(Box::from(ChunkError::InvalidChunkType))
+        if options.strict {
+            chunk_type.validate_with_reason().map_err(ChunkError::from)?;
+        } else if let Some(index) = chunk_type_bytes.iter().position(|b| !b.is_ascii_alphabetic()) {
+            return Err(Box::from(ChunkError::from(ChunkTypeError::InvalidCharacter(index))))
+        }
+        // reject oversized chunks before looking at the data
+        if let Some(max_data) = options.max_data_length {
+            if data_length > max_data {
+                return Err(Box::from(ChunkError::ChunkTooLarge(data_length, max_data)))
+            }
         }
-        // next 4 bytes is the message
+        // the message and CRC must fit in what is left
+        if data_length > bytes.len() - Chunk::CRC_BYTES {
+            return Err(Box::from(ChunkError::InputTooSmall))
+        }
+        // next `data_length` bytes is the message
         let (message_bytes, bytes) = bytes.split_at(data_length);
         // last 4 bytes are the CRC, disregard last splitting of bytes
         let (crc_bytes, _) = bytes.split_at(Chunk::CRC_BYTES);
+        let expected_crc = u32::from_be_bytes(crc_bytes.try_into()?);
 
         let new = Self {
-            chunk_type: chunk_type,
-            message_bytes: message_bytes.into()
+            chunk_type,
+            message_bytes: message_bytes.into(),
+            stored_crc: Some(expected_crc),
         };
 
         // validated crc
-        let actual_crc = new.crc();
-        let expected_crc = u32::from_be_bytes(crc_bytes.try_into()?);
+        if options.verify_crc {
+            let actual_crc = new.crc();
+
+            if expected_crc != actual_crc {
+                return Err(Box::from(ChunkError::InvalidCrc(expected_crc, actual_crc)));
+            }
+        }
+        Ok(new)
+    }
 
+    /*
+        Reads a single strictly valid Chunk from `reader`. A stream that is
+        already at its end gives `ChunkError::Eof`, one that ends partway
+        through a chunk gives `ChunkError::UnexpectedEof`.
+    */
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        Chunk::from_reader_limited(reader, usize::MAX)
+    }
+
+    /*
+        Like `from_reader`, but the length field is checked against `max_data`
+        before any data is read, so a hostile length can't make us buffer more
+        than `max_data` bytes.
+    */
+    pub fn from_reader_limited<R: Read>(reader: &mut R, max_data: usize) -> Result<Self> {
+        let mut header = [0; Chunk::DATA_LENGTH_BYTES + Chunk::CHUNK_TYPE_BYTES];
+        match read_up_to(reader, &mut header)? {
+            0 => return Err(Box::from(ChunkError::Eof)),
+            got if got < header.len() => {
+                return Err(Box::from(ChunkError::UnexpectedEof { expected: header.len(), got }))
+            }
+            _ => {}
+        }
+        let (data_length, chunk_type_bytes) = header.split_at(Chunk::DATA_LENGTH_BYTES);
+        let data_length = u32::from_be_bytes(data_length.try_into()?) as usize;
+        let chunk_type_bytes: [u8; 4] = chunk_type_bytes.try_into()?;
+        let chunk_type = ChunkType::try_from(chunk_type_bytes)?;
+
+        chunk_type.validate_with_reason().map_err(ChunkError::from)?;
+        if data_length > max_data {
+            return Err(Box::from(ChunkError::ChunkTooLarge(data_length, max_data)))
+        }
+
+        // read through `take` so the buffer only grows as data actually arrives
+        let mut message_bytes = Vec::new();
+        let got = reader.by_ref().take(data_length as u64).read_to_end(&mut message_bytes)?;
+        if got < data_length {
+            return Err(Box::from(ChunkError::UnexpectedEof { expected: data_length, got }));
+        }
+        let mut crc_bytes = [0; Chunk::CRC_BYTES];
+        let got = read_up_to(reader, &mut crc_bytes)?;
+        if got < crc_bytes.len() {
+            return Err(Box::from(ChunkError::UnexpectedEof { expected: crc_bytes.len(), got }));
+        }
+
+        let expected_crc = u32::from_be_bytes(crc_bytes);
+        let new = Self { chunk_type, message_bytes, stored_crc: Some(expected_crc) };
+        let actual_crc = new.crc();
         if expected_crc != actual_crc {
             return Err(Box::from(ChunkError::InvalidCrc(expected_crc, actual_crc)));
         }
@@ -115,15 +623,98 @@ impl TryFrom<&[u8]> for Chunk {
     }
 }
 
+// Checks a Latin-1 encoded text chunk keyword against the PNG spec rules
+fn validate_keyword(keyword: &[u8]) -> std::result::Result<(), &'static str> {
+    if keyword.is_empty() || keyword.len() > 79 {
+        return Err("keywords must be 1 to 79 characters long");
+    }
+    if !keyword.iter().all(|&b| (32..=126).contains(&b) || b >= 161) {
+        return Err("keywords may only contain printable Latin-1 characters");
+    }
+    if keyword.starts_with(b" ") || keyword.ends_with(b" ") || keyword.windows(2).any(|w| w == b"  ") {
+        return Err("keywords may not have leading, trailing or consecutive spaces");
+    }
+    Ok(())
+}
+
+// ASCII is valid utf-8, so only other Latin-1 text has to be re-encoded
+fn latin1_into_string(bytes: Vec<u8>) -> Result<String> {
+    if bytes.is_ascii() {
+        Ok(String::from_utf8(bytes)?)
+    } else {
+        TextEncoding::Latin1.decode(&bytes)
+    }
+}
+
+// Shorthand for rejecting a malformed text chunk in validate_text
+fn invalid_text(reason: String) -> Result<()> {
+    Err(Box::from(ChunkError::InvalidTextChunk(reason)))
+}
+
+// Fills as much of `buf` as the stream has left and returns how much that was,
+// so callers can tell an empty stream from one that ends partway through
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut got = 0;
+    while got < buf.len() {
+        match reader.read(&mut buf[got..]) {
+            Ok(0) => break,
+            Ok(n) => got += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Box::from(e)),
+        }
+    }
+    Ok(got)
+}
+
+impl TryFrom<&[u8]> for Chunk {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        Chunk::parse(bytes, &ParseOptions::strict())
+    }
+}
+
+/// Controls how forgiving parsing is about malformed input.
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptions {
+    /// Enforce the PNG signature, fully valid chunk types, no trailing bytes
+    /// and the structural rules of `Png::validate`.
+    pub strict: bool,
+    /// Check every chunk's CRC against its contents.
+    pub verify_crc: bool,
+    /// Reject any chunk whose declared data length is larger than this.
+    pub max_data_length: Option<usize>,
+}
+
+impl ParseOptions {
+    pub fn strict() -> Self {
+        Self { strict: true, verify_crc: true, max_data_length: None }
+    }
+
+    pub fn lenient() -> Self {
+        Self { strict: false, verify_crc: true, max_data_length: None }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::lenient()
+    }
+}
+
+/// Which CRC to emit when writing a chunk that was parsed from bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CrcMode {
+    /// Compute the CRC from the chunk's current type and data.
+    #[default]
+    Recompute,
+    /// Write back the CRC the chunk was parsed with, even if it is wrong.
+    Preserve,
+}
+
 impl Display for Chunk {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "Chunk {{",)?;
-        writeln!(f, "  Length: {}", self.length())?;
-        writeln!(f, "  Type: {}", self.chunk_type())?;
-        writeln!(f, "  Data: {} bytes", self.data().len())?;
-        writeln!(f, "  Crc: {}", self.crc())?;
-        writeln!(f, "}}",)?;
-        Ok(())
+        self.write_with(f, self.chunk_type(), None, &[])
     }
 }
 
@@ -136,11 +727,48 @@ pub enum ChunkError {
     // Input String is too small to be a valid Chunk, 12 bytes minimum
     InputTooSmall,
 
-    // Chunk Type is invalid
-    InvalidChunkType
+    // Chunk Type is invalid, with the reason
+    InvalidChunkType(ChunkTypeError),
+
+    // Declared data length exceeds the allowed maximum
+    ChunkTooLarge(usize, usize),
+
+    // Text chunk keyword breaks the spec rules, with the reason
+    InvalidKeyword(String),
+
+    // Chunk type is critical, so viewers would reject a chunk storing arbitrary data
+    CriticalChunkType(ChunkType),
+
+    // Text chunk layout breaks the spec, with the reason
+    InvalidTextChunk(String),
+
+    // validate_text was called on a chunk that isn't tEXt, zTXt or iTXt
+    NotTextChunk(ChunkType),
+
+    // Compressed text could not be decompressed, with the reason
+    InvalidCompressedData(String),
+
+    // Stream ended cleanly before the first byte of a chunk
+    Eof,
+
+    // Stream ended partway through a chunk field
+    UnexpectedEof { expected: usize, got: usize },
 }
 
-impl std::error::Error for ChunkError {}
+impl std::error::Error for ChunkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChunkError::InvalidChunkType(reason) => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+impl From<ChunkTypeError> for ChunkError {
+    fn from(err: ChunkTypeError) -> Self {
+        ChunkError::InvalidChunkType(err)
+    }
+}
 
 impl Display for ChunkError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -154,8 +782,32 @@ impl Display for ChunkError {
             ChunkError::InputTooSmall => {
                 write!(f, "Input String is too small to be a valid Chunk")
             },
-            ChunkError::InvalidChunkType => {
-                write!(f, "Invalid ChunkType")
+            ChunkError::InvalidChunkType(reason) => {
+                write!(f, "Invalid ChunkType: {}", reason)
+            },
+            ChunkError::ChunkTooLarge(length, max) => {
+                write!(f, "Chunk data length {} exceeds the maximum of {}", length, max)
+            },
+            ChunkError::InvalidKeyword(reason) => {
+                write!(f, "Invalid text keyword: {}", reason)
+            },
+            ChunkError::CriticalChunkType(chunk_type) => {
+                write!(f, "Chunk type {} is critical, use an ancillary type (lowercase first letter) to store data", chunk_type)
+            },
+            ChunkError::InvalidTextChunk(reason) => {
+                write!(f, "Invalid text chunk: {}", reason)
+            },
+            ChunkError::NotTextChunk(chunk_type) => {
+                write!(f, "Chunk type {} is not a text chunk (tEXt, zTXt or iTXt)", chunk_type)
+            },
+            ChunkError::InvalidCompressedData(reason) => {
+                write!(f, "Compressed text could not be decompressed: {}", reason)
+            },
+            ChunkError::Eof => {
+                write!(f, "End of stream, no more chunks")
+            },
+            ChunkError::UnexpectedEof { expected, got } => {
+                write!(f, "Stream ended partway through a chunk: expected {} more bytes, got {}", expected, got)
             }
         }
     }
@@ -164,7 +816,7 @@ impl Display for ChunkError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::chunk_type::ChunkType;
+    use std::error::Error as _;
     use std::str::FromStr;
 
     fn testing_chunk() -> Chunk {
@@ -185,6 +837,463 @@ mod tests {
         Chunk::try_from(chunk_data.as_ref()).unwrap()
     }
 
+    #[test]
+    fn test_chunk_serialized_len() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.serialized_len(), chunk.as_bytes().len());
+
+        let empty = Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]);
+        assert_eq!(empty.serialized_len(), empty.as_bytes().len());
+    }
+
+    #[test]
+    fn test_data_entropy() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(Chunk::new(chunk_type, vec![]).data_entropy(), 0.0);
+        assert_eq!(Chunk::new(chunk_type, vec![7; 100]).data_entropy(), 0.0);
+        assert_eq!(Chunk::new(chunk_type, b"abababab".to_vec()).data_entropy(), 1.0);
+        let every_byte: Vec<u8> = (0..=255).collect();
+        assert!((Chunk::new(chunk_type, every_byte).data_entropy() - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chunk_summary_line() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.summary_line(), "RuSt |         42 | 2882656334");
+
+        let short = Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]);
+        assert_eq!(short.summary_line().len(), chunk.summary_line().len());
+        assert_eq!(chunk.summary_line_with(&"[RuSt]"), "[RuSt] |         42 | 2882656334");
+    }
+
+    #[test]
+    fn test_chunk_write_with() {
+        let chunk = testing_chunk();
+        let mut plain = String::new();
+        chunk.write_with(&mut plain, chunk.chunk_type(), None, &[]).unwrap();
+        assert_eq!(plain, chunk.to_string());
+
+        let mut text = String::new();
+        chunk.write_with(&mut text, &"[RuSt]", Some(&"decoded"), &[("Entropy", "4.000".to_string())]).unwrap();
+        assert!(text.contains("  Type: [RuSt]\n  Data: decoded\n"));
+
+        // Display never decodes the data, even for chunks the color module understands
+        let gama = Chunk::new(ChunkType::from_str("gAMA").unwrap(), 45455u32.to_be_bytes().to_vec());
+        assert!(gama.to_string().contains("  Data: 4 bytes\n"));
+        assert!(text.contains("  Entropy: 4.000\n  Crc: 2882656334\n"));
+    }
+
+    #[test]
+    fn test_chunk_with_capacity() {
+        let mut chunk = Chunk::with_capacity(ChunkType::from_str("RuSt").unwrap(), 42);
+        assert_eq!(chunk.length(), 0);
+
+        chunk.extend_data(b"This is where your secret ");
+        chunk.extend_data(b"message will be!");
+        assert_eq!(chunk, testing_chunk());
+    }
+
+    #[test]
+    fn test_compute_crc() {
+        let chunk = testing_chunk();
+        assert_eq!(Chunk::compute_crc(chunk.chunk_type(), chunk.data()), chunk.crc());
+
+        let renamed = ChunkType::from_str("ruSt").unwrap();
+        let expected = Chunk::compute_crc(&renamed, chunk.data());
+        assert_eq!(chunk.map_type(|_| renamed).crc(), expected);
+    }
+
+    #[test]
+    fn test_chunk_set_data() {
+        let mut chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"old".to_vec());
+        let old = chunk.set_data(b"This is where your secret message will be!".to_vec());
+        assert_eq!(old, b"old");
+        assert_eq!(chunk, testing_chunk());
+        assert_eq!(chunk.crc(), 2882656334);
+    }
+
+    #[test]
+    fn test_chunk_set_chunk_type() {
+        let bytes = testing_chunk().as_bytes();
+        let mut chunk = Chunk::parse(&bytes, &ParseOptions::strict()).unwrap();
+        let old = chunk.set_chunk_type(ChunkType::from_str("ruSt").unwrap());
+        assert_eq!(old.to_string(), "RuSt");
+        assert_eq!(chunk.stored_crc(), None);
+        assert_eq!(chunk.data(), testing_chunk().data());
+        assert_ne!(chunk.crc(), 2882656334);
+    }
+
+    #[test]
+    fn test_chunk_map_type() {
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), testing_chunk().data().to_vec());
+        let mapped = chunk.map_type(|t| {
+            let [a, b, c, d] = t.bytes();
+            ChunkType::try_from([a.to_ascii_uppercase(), b, c, d]).unwrap()
+        });
+        assert_eq!(mapped.chunk_type().to_string(), "RuSt");
+        assert_eq!(mapped.crc(), 2882656334);
+        assert_eq!(mapped.as_bytes(), testing_chunk().as_bytes());
+    }
+
+    #[test]
+    fn test_chunk_eq() {
+        assert_eq!(testing_chunk(), testing_chunk());
+
+        let other = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"Another message".to_vec());
+        assert_ne!(testing_chunk(), other);
+    }
+
+    #[test]
+    fn test_chunk_crc_input() {
+        let chunk = testing_chunk();
+        let input = chunk.crc_input();
+        assert_eq!(&input[..4], b"RuSt");
+        assert_eq!(&input[4..], chunk.data());
+        assert_eq!(crc::crc32::checksum_ieee(&input), chunk.crc());
+    }
+
+    #[test]
+    fn test_zero_length_chunk() {
+        let chunk = Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]);
+        assert_eq!(chunk.length(), 0);
+        assert_eq!(chunk.crc_input(), b"IEND");
+        assert_eq!(chunk.crc(), crc::crc32::checksum_ieee(b"IEND"));
+        assert_eq!(chunk.crc(), 0xAE42_6082);
+
+        let bytes = chunk.as_bytes();
+        assert_eq!(bytes, vec![0, 0, 0, 0, 73, 69, 78, 68, 0xAE, 0x42, 0x60, 0x82]);
+        assert_eq!(bytes.len(), Chunk::MINIUMUM_LENGTH);
+        assert_eq!(chunk.serialized_len(), Chunk::MINIUMUM_LENGTH);
+    }
+
+    #[test]
+    fn test_zero_length_chunk_parse() {
+        let bytes = [0, 0, 0, 0, 73, 69, 78, 68, 0xAE, 0x42, 0x60, 0x82];
+        let chunk = Chunk::try_from(bytes.as_ref()).unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "IEND");
+        assert!(chunk.data().is_empty());
+        assert_eq!(chunk.as_bytes(), bytes);
+
+        // trailing bytes after a zero-length chunk belong to the next chunk
+        let mut with_trailing = bytes.to_vec();
+        with_trailing.extend_from_slice(b"next");
+        assert_eq!(Chunk::try_from(with_trailing.as_ref()).unwrap(), chunk);
+
+        let mut bad_crc = bytes;
+        bad_crc[11] ^= 0xFF;
+        assert!(Chunk::try_from(bad_crc.as_ref()).is_err());
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_chunk_data_base64() {
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"hello".to_vec());
+        assert_eq!(chunk.data_base64(), "aGVsbG8=");
+    }
+
+    #[test]
+    fn test_chunk_text_keyword() {
+        let chunk = Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Title\0Dice".to_vec());
+        assert_eq!(chunk.text_keyword(), Some("Title"));
+
+        let chunk = Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Title".to_vec());
+        assert_eq!(chunk.text_keyword(), None);
+    }
+
+    #[test]
+    fn test_chunk_truncated_data() {
+        let mut chunk_data = testing_chunk().as_bytes();
+        chunk_data.truncate(20);
+        assert!(Chunk::try_from(chunk_data.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_lenient_chunk_reserved_bit() {
+        let chunk = Chunk::new(ChunkType::try_from(*b"Rust").unwrap(), vec![1, 2, 3]);
+        let bytes = chunk.as_bytes();
+        assert!(Chunk::parse(&bytes, &ParseOptions::strict()).is_err());
+        assert!(Chunk::parse(&bytes, &ParseOptions::lenient()).is_ok());
+    }
+
+    #[test]
+    fn test_parse_max_data_length() {
+        let bytes = testing_chunk().as_bytes();
+        let options = ParseOptions { max_data_length: Some(41), ..ParseOptions::strict() };
+        let err = Chunk::parse(&bytes, &options).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::ChunkTooLarge(42, 41))));
+
+        let options = ParseOptions { max_data_length: Some(42), ..ParseOptions::strict() };
+        assert!(Chunk::parse(&bytes, &options).is_ok());
+    }
+
+    #[test]
+    fn test_from_reader_limited() {
+        let bytes = testing_chunk().as_bytes();
+        let chunk = Chunk::from_reader_limited(&mut bytes.as_slice(), 42).unwrap();
+        assert_eq!(chunk, testing_chunk());
+
+        let err = Chunk::from_reader_limited(&mut bytes.as_slice(), 41).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::ChunkTooLarge(42, 41))));
+
+        // a huge declared length is rejected without reading further
+        let mut hostile = bytes.clone();
+        hostile[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let err = Chunk::from_reader_limited(&mut hostile.as_slice(), 1024).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::ChunkTooLarge(_, 1024))));
+
+        let err = Chunk::from_reader_limited(&mut &bytes[..20], 1024).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::UnexpectedEof { expected: 42, got: 12 })));
+    }
+
+    #[test]
+    fn test_from_reader_eof() {
+        let bytes = testing_chunk().as_bytes();
+        let mut stream = bytes.repeat(2);
+        let mut reader = stream.as_slice();
+        assert_eq!(Chunk::from_reader(&mut reader).unwrap(), testing_chunk());
+        assert_eq!(Chunk::from_reader(&mut reader).unwrap(), testing_chunk());
+        let err = Chunk::from_reader(&mut reader).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::Eof)));
+
+        // cut inside the length, the type, the data and the crc
+        stream.truncate(bytes.len());
+        for (cut, expected, got) in [(2, 8, 2), (6, 8, 6), (8, 42, 0), (30, 42, 22), (50, 4, 0), (53, 4, 3)] {
+            let err = Chunk::from_reader(&mut &stream[..cut]).unwrap_err();
+            match err.downcast_ref::<ChunkError>() {
+                Some(ChunkError::UnexpectedEof { expected: e, got: g }) => assert_eq!((*e, *g), (expected, got), "cut at {}", cut),
+                other => panic!("unexpected error {:?} for cut at {}", other, cut),
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_corrupt_crc() {
+        let chunk = testing_chunk();
+        let crc = chunk.crc();
+        let bytes = testing_chunk().with_corrupt_crc();
+        assert_eq!(bytes, testing_chunk().with_corrupt_crc());
+        assert_eq!(bytes[..bytes.len() - 4], chunk.as_bytes()[..bytes.len() - 4]);
+
+        let err = Chunk::try_from(bytes.as_ref()).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InvalidCrc(e, a)) if *e == !crc && *a == crc));
+    }
+
+    #[test]
+    fn test_chunk_data_chunks() {
+        let chunk = testing_chunk();
+        let pieces: Vec<&[u8]> = chunk.data_chunks(16).collect();
+        assert_eq!(pieces.iter().map(|p| p.len()).collect::<Vec<_>>(), vec![16, 16, 10]);
+        assert_eq!(pieces.concat(), chunk.data());
+
+        assert_eq!(chunk.data_chunks(0).count(), 0);
+        assert_eq!(chunk.data_chunks(100).count(), 1);
+    }
+
+    #[test]
+    fn test_chunk_crc_bytes() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.crc_bytes(), 2882656334u32.to_be_bytes());
+        assert_eq!(chunk.as_bytes()[chunk.serialized_len() - 4..], chunk.crc_bytes());
+    }
+
+    #[test]
+    fn test_chunk_iter_bytes() {
+        let chunk = testing_chunk();
+        let bytes: Vec<u8> = chunk.iter_bytes().collect();
+        assert_eq!(bytes, chunk.as_bytes());
+        assert_eq!(chunk.iter_bytes().count(), chunk.serialized_len());
+    }
+
+    #[test]
+    fn test_new_checked() {
+        let data = b"secret".to_vec();
+        assert!(Chunk::new_checked(ChunkType::from_str("ruSt").unwrap(), data.clone()).is_ok());
+        assert!(Chunk::new_checked(ChunkType::from_str("rUSt").unwrap(), data.clone()).is_ok());
+
+        let err = Chunk::new_checked(ChunkType::from_str("RuSt").unwrap(), data.clone()).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::CriticalChunkType(_))));
+
+        let err = Chunk::new_checked(ChunkType::from_str("rust").unwrap(), data).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ChunkError>(),
+            Some(ChunkError::InvalidChunkType(ChunkTypeError::ReservedBitInvalid))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_chunk_type_source() {
+        let mut bytes = testing_chunk().as_bytes();
+        bytes[6] = b'1';
+        for options in [ParseOptions::strict(), ParseOptions::lenient()] {
+            let err = Chunk::parse(&bytes, &options).unwrap_err();
+            let err = err.downcast_ref::<ChunkError>().unwrap();
+            assert!(matches!(err, ChunkError::InvalidChunkType(ChunkTypeError::InvalidCharacter(2))));
+            assert!(err.source().unwrap().is::<ChunkTypeError>());
+        }
+
+        let bytes = Chunk::new(ChunkType::from_str("Rust").unwrap(), vec![]).as_bytes();
+        let err = Chunk::parse(&bytes, &ParseOptions::strict()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ChunkError>(),
+            Some(ChunkError::InvalidChunkType(ChunkTypeError::ReservedBitInvalid))
+        ));
+    }
+
+    #[test]
+    fn test_chunk_write_to() {
+        let chunk = testing_chunk();
+        let mut bytes = Vec::new();
+        let written = chunk.write_to(&mut bytes).unwrap();
+        assert_eq!(written, chunk.serialized_len());
+        assert_eq!(bytes, chunk.as_bytes());
+    }
+
+    #[test]
+    fn test_stored_crc() {
+        let bytes = testing_chunk().with_corrupt_crc();
+        let options = ParseOptions { verify_crc: false, ..ParseOptions::lenient() };
+        let mut chunk = Chunk::parse(&bytes, &options).unwrap();
+
+        assert_eq!(chunk, testing_chunk());
+        assert_eq!(chunk.stored_crc(), Some(!chunk.crc()));
+        assert_eq!(chunk.crc_for(CrcMode::Preserve), !chunk.crc());
+        assert_eq!(chunk.crc_for(CrcMode::Recompute), chunk.crc());
+
+        let mut preserved = Vec::new();
+        chunk.write_to_with(&mut preserved, CrcMode::Preserve).unwrap();
+        assert_eq!(preserved, bytes);
+        assert_eq!(chunk.as_bytes(), testing_chunk().as_bytes());
+
+        chunk.extend_data(b"!");
+        assert_eq!(chunk.stored_crc(), None);
+        assert_eq!(chunk.crc_for(CrcMode::Preserve), chunk.crc());
+    }
+
+    #[test]
+    fn test_new_text() {
+        let chunk = Chunk::new_text("Comment", "café").unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "tEXt");
+        assert_eq!(chunk.data(), b"Comment\0caf\xE9");
+        assert_eq!(chunk.as_text(), Some(("Comment".to_string(), "café".to_string())));
+    }
+
+    #[test]
+    fn test_new_text_invalid_keyword() {
+        for keyword in ["", " Title", "Title ", "Two  spaces", "Tab\there", &"k".repeat(80)] {
+            let err = Chunk::new_text(keyword, "text").unwrap_err();
+            assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InvalidKeyword(_))), "{:?}", keyword);
+        }
+        assert!(Chunk::new_text("Emoji \u{1F600}", "text").is_err());
+        assert!(Chunk::new_text(&"k".repeat(79), "text").is_ok());
+    }
+
+    #[test]
+    fn test_new_text_bytes() {
+        let chunk = Chunk::new_text_bytes(b"Author", b"Ren\xE9e").unwrap();
+        assert_eq!(chunk.data(), b"Author\0Ren\xE9e");
+        assert!(chunk.data_as_string().is_err());
+        assert_eq!(chunk.as_text(), Some(("Author".to_string(), "Renée".to_string())));
+
+        assert!(Chunk::new_text_bytes(b"Caf\xE9", b"").is_ok());
+        assert!(Chunk::new_text_bytes(b"Bad\x7F", b"").is_err());
+        assert!(Chunk::new_text_bytes(b"Bad\xA0", b"").is_err());
+        assert!(Chunk::new_text_bytes(b"", b"value").is_err());
+    }
+
+    #[test]
+    fn test_new_text_null_in_value() {
+        let err = Chunk::new_text_bytes(b"Author", b"Ren\0e").unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InvalidTextChunk(_))));
+        assert!(Chunk::new_text("Comment", "a\0b").is_err());
+        assert!(Chunk::new_text("Comment", "ab").unwrap().validate_text().is_ok());
+    }
+
+    #[test]
+    fn test_as_text_other_chunk() {
+        assert_eq!(testing_chunk().as_text(), None);
+        let chunk = Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"no separator".to_vec());
+        assert_eq!(chunk.as_text(), None);
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_ztxt_round_trip() {
+        let chunk = Chunk::new_ztxt("Comment", &"café ".repeat(20)).unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "zTXt");
+        assert!(chunk.data().starts_with(b"Comment\0\0"));
+        assert!(chunk.length() < 100);
+        assert!(chunk.validate_text().is_ok());
+        assert_eq!(chunk.as_ztxt(100).unwrap(), ("Comment".to_string(), "café ".repeat(20)));
+
+        assert!(Chunk::new_ztxt(" Comment", "text").is_err());
+        assert!(Chunk::new_text("Comment", "text").unwrap().as_ztxt(100).is_err());
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_ztxt_output_limit() {
+        // a few hundred bytes of zlib that expand to a megabyte
+        let chunk = Chunk::new_ztxt("Comment", &"a".repeat(1024 * 1024)).unwrap();
+        assert!(chunk.length() < 2048);
+        let err = chunk.as_ztxt(1024).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InvalidCompressedData(_))));
+        assert_eq!(chunk.as_ztxt(1024 * 1024).unwrap().1.len(), 1024 * 1024);
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_ztxt_corrupt_data() {
+        let chunk = Chunk::new(ChunkType::from_str("zTXt").unwrap(), b"Comment\0\0not zlib".to_vec());
+        let err = chunk.as_ztxt(100).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InvalidCompressedData(_))));
+    }
+
+    #[test]
+    fn test_try_into_text() {
+        let chunk = Chunk::new_text("Comment", "café").unwrap();
+        assert_eq!(chunk.as_text(), Some(("Comment".to_string(), "café".to_string())));
+        assert_eq!(chunk.try_into_text().unwrap(), ("Comment".to_string(), "café".to_string()));
+
+        let chunk = Chunk::new_text("Title", "").unwrap();
+        assert_eq!(chunk.try_into_text().unwrap(), ("Title".to_string(), String::new()));
+
+        let chunk = Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"no separator".to_vec());
+        assert!(chunk.try_into_text().is_err());
+        let err = testing_chunk().try_into_text().unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::NotTextChunk(_))));
+    }
+
+    #[test]
+    fn test_validate_text() {
+        let text = |chunk_type: &str, data: &[u8]| {
+            Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec()).validate_text()
+        };
+        assert!(Chunk::new_text("Comment", "hello").unwrap().validate_text().is_ok());
+        assert!(text("tEXt", b"Comment\0two\0nulls").is_err());
+        assert!(text("tEXt", b"no separator").is_err());
+        assert!(text("tEXt", b" Comment\0text").is_err());
+
+        assert!(text("zTXt", b"Comment\0\0\x78\x9c\0").is_ok());
+        assert!(text("zTXt", b"Comment\0").is_err());
+        assert!(text("zTXt", b"Comment\0\x01").is_err());
+
+        assert!(text("iTXt", "Title\0\0\0en\0Titel\0Grüße".as_bytes()).is_ok());
+        assert!(text("iTXt", b"Title\0\0\0en\0Titel").is_err());
+        assert!(text("iTXt", b"Title\0\0\0en\0Titel\0\xff").is_err());
+        assert!(text("iTXt", b"Title\0\x02\0en\0Titel\0text").is_err());
+
+        let err = testing_chunk().validate_text().unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::NotTextChunk(_))));
+    }
+
+    #[test]
+    fn test_lenient_chunk_skip_crc() {
+        let bytes = testing_chunk().with_corrupt_crc();
+        let options = ParseOptions { verify_crc: false, ..ParseOptions::lenient() };
+        assert!(Chunk::parse(&bytes, &ParseOptions::lenient()).is_err());
+        assert!(Chunk::parse(&bytes, &options).is_ok());
+    }
+
     #[test]
     fn test_chunk_length() {
         let chunk = testing_chunk();
@@ -2,10 +2,37 @@ use std::str::FromStr;
 use std::convert::TryFrom;
 use crate::Error;
 use std::fmt::Display;
+use std::borrow::Borrow;
+use std::hash::{Hash, Hasher};
 
 // Implementation for Chapter 1 of PNGme
 
-#[derive(Eq, PartialEq, Debug)]
+// Chunk types defined by the PNG spec, with a one-line description of each
+const STANDARD_TYPES: [(&str, &str); 19] = [
+    ("IHDR", "Image header"),
+    ("PLTE", "Palette"),
+    ("IDAT", "Image data"),
+    ("IEND", "Image trailer"),
+    ("tRNS", "Transparency"),
+    ("cHRM", "Primary chromaticities and white point"),
+    ("gAMA", "Image gamma"),
+    ("iCCP", "Embedded ICC profile"),
+    ("sBIT", "Significant bits"),
+    ("sRGB", "Standard RGB color space"),
+    ("tEXt", "Textual data"),
+    ("zTXt", "Compressed textual data"),
+    ("iTXt", "International textual data"),
+    ("bKGD", "Background color"),
+    ("hIST", "Image histogram"),
+    ("pHYs", "Physical pixel dimensions"),
+    ("sPLT", "Suggested palette"),
+    ("tIME", "Image last-modification time"),
+    ("eXIf", "Exchangeable image file format metadata"),
+];
+
+/// Chunk types order by their bytes, so uppercase (critical) letters sort
+/// before lowercase ones: `IDAT` < `IEND` < `IHDR` < `gAMA` < `tEXt`.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub struct ChunkType {
     bytes: [u8; 4]
 }
@@ -15,11 +42,24 @@ impl ChunkType {
         self.bytes
     }
 
+    pub fn bytes_ref(&self) -> &[u8; 4] {
+        &self.bytes
+    }
+
     pub fn is_valid(&self) -> bool {
-        let valid_chars = self.bytes.
-            iter()
-            .all(|&b| b.is_ascii_lowercase() || b.is_ascii_uppercase());
-        valid_chars && self.is_reserved_bit_valid()
+        self.validate_with_reason().is_ok()
+    }
+
+    /// Like `is_valid`, but explains why a chunk type is invalid: either a
+    /// byte that is not an ASCII letter or a lowercase reserved bit.
+    pub fn validate_with_reason(&self) -> Result<(), ChunkTypeError> {
+        if let Some(index) = self.bytes.iter().position(|b| !b.is_ascii_alphabetic()) {
+            return Err(ChunkTypeError::InvalidCharacter(index));
+        }
+        if !self.is_reserved_bit_valid() {
+            return Err(ChunkTypeError::ReservedBitInvalid);
+        }
+        Ok(())
     }
 
     pub fn is_critical(&self) -> bool {
@@ -37,37 +77,146 @@ impl ChunkType {
     pub fn is_safe_to_copy(&self) -> bool {
         self.bytes[3].is_ascii_lowercase()
     }
+
+    /// All four property bits at once, as reported by the predicates above.
+    pub fn property_bits(&self) -> PropertyBits {
+        PropertyBits {
+            critical: self.is_critical(),
+            public: self.is_public(),
+            reserved_valid: self.is_reserved_bit_valid(),
+            safe_to_copy: self.is_safe_to_copy(),
+        }
+    }
+
+    /// The chunk type as a big-endian `u32`, i.e. the first byte is the most significant.
+    pub fn to_u32(&self) -> u32 {
+        u32::from_be_bytes(self.bytes)
+    }
+
+    /// Creates a chunk type from a big-endian `u32`, i.e. the most significant
+    /// byte becomes the first byte. Every byte must be an ASCII letter.
+    pub fn from_u32(n: u32) -> Result<ChunkType, Error> {
+        ChunkType::try_from(&n.to_be_bytes()[..])
+    }
+
+    /// Builds an ancillary, private, safe-to-copy chunk type from the first four
+    /// letters of `name`, padding shorter names with `x`. The casing of each
+    /// letter is overridden to set those property bits.
+    pub fn new_private(name: &str) -> Result<ChunkType, Error> {
+        if name.is_empty() {
+            return Err(Box::new(ChunkTypeError::ByteLengthError(0, String::new())));
+        }
+        validate_bytes(name.as_bytes())?;
+
+        let mut bytes = [b'x'; 4];
+        for (byte, letter) in bytes.iter_mut().zip(name.bytes()) {
+            *byte = letter.to_ascii_lowercase();
+        }
+        bytes[2] = bytes[2].to_ascii_uppercase();
+        ChunkType::try_from(bytes)
+    }
+
+    /// Builds an ancillary chunk type from a 4 letter `name`. Unlike
+    /// `new_private`, only the first letter is recased (to lowercase, making
+    /// the chunk ancillary); the public, reserved and safe-to-copy bits keep
+    /// the case they were given in, and the name is never padded or truncated.
+    pub fn new_ancillary(name: &str) -> Result<ChunkType, Error> {
+        let mut chunk_type = ChunkType::from_str(name)?;
+        chunk_type.bytes[0] = chunk_type.bytes[0].to_ascii_lowercase();
+        Ok(chunk_type)
+    }
+
+    /// The chunk types defined by the PNG spec paired with a short description.
+    pub fn reserved_standard_types() -> &'static [(&'static str, &'static str)] {
+        &STANDARD_TYPES
+    }
+
+    /// Whether this is one of the chunk types defined by the PNG spec.
+    pub fn is_standard(&self) -> bool {
+        self.standard_description().is_some()
+    }
+
+    /// A short description of this chunk type if it is defined by the PNG spec.
+    pub fn standard_description(&self) -> Option<&'static str> {
+        STANDARD_TYPES
+            .iter()
+            .find(|(name, _)| name.as_bytes() == self.bytes)
+            .map(|&(_, description)| description)
+    }
+}
+
+/// The properties encoded in the case of each chunk type letter.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct PropertyBits {
+    /// First letter uppercase: the chunk is needed to display the image.
+    pub critical: bool,
+    /// Second letter uppercase: the type is defined by the PNG spec or registered.
+    pub public: bool,
+    /// Third letter uppercase, as every current chunk type must have.
+    pub reserved_valid: bool,
+    /// Fourth letter lowercase: editors may copy the chunk after changing critical chunks.
+    pub safe_to_copy: bool,
 }
 
 impl TryFrom<[u8; 4]> for ChunkType {
     type Error = Error;
 
     fn try_from(bytes: [u8; 4]) -> Result<Self, Self::Error> {
-            Ok(ChunkType { bytes: bytes })
+            Ok(ChunkType { bytes })
     }
 }
 
-impl FromStr for ChunkType {
-    type Err = Error;
-    fn from_str(byte_str: &str) -> Result<Self, Self::Err> {
-            let byte_arr = byte_str.as_bytes();
+impl AsRef<[u8]> for ChunkType {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
 
-            // must be a string of length 4
-            if byte_arr.len() != 4 {
-                return Err(Box::new(ChunkTypeError::ByteLengthError(byte_arr.len())));
-            }
+/// Lets a `HashMap<ChunkType, _>` be probed with a `&[u8]` such as `b"IHDR"`.
+impl Borrow<[u8]> for ChunkType {
+    fn borrow(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+// Every byte must be an ASCII letter; the error names the first one that isn't
+fn validate_bytes(bytes: &[u8]) -> Result<(), Error> {
+    match bytes.iter().position(|b| !b.is_ascii_alphabetic()) {
+        Some(index) => Err(Box::new(ChunkTypeError::InvalidCharacter(index))),
+        None => Ok(()),
+    }
+}
 
-            // validate each byte
-            let valid_bytes = byte_arr
-                .iter()
-                .all(|&b| b.is_ascii_lowercase() || b.is_ascii_uppercase());
+// Hashed as a slice rather than an array so that equal ChunkType and [u8]
+// keys hash the same, as the Borrow contract requires.
+impl Hash for ChunkType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes[..].hash(state);
+    }
+}
+
+/// Checks the length and that every byte is an ASCII letter, so a slice cut
+/// out of a larger buffer can be used directly.
+impl TryFrom<&[u8]> for ChunkType {
+    type Error = Error;
 
-            if !valid_bytes {
-                return Err(Box::new(ChunkTypeError::ByteLengthError(byte_arr.len())));
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+            // must be exactly 4 bytes
+            if bytes.len() != 4 {
+                let input = String::from_utf8_lossy(bytes).into_owned();
+                return Err(Box::new(ChunkTypeError::ByteLengthError(bytes.len(), input)));
             }
 
+            validate_bytes(bytes)?;
 
-            Ok(ChunkType::try_from([byte_arr[0], byte_arr[1], byte_arr[2], byte_arr[3]])?)
+            ChunkType::try_from([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+}
+
+impl FromStr for ChunkType {
+    type Err = Error;
+    fn from_str(byte_str: &str) -> Result<Self, Self::Err> {
+            ChunkType::try_from(byte_str.as_bytes())
     }
 }
 
@@ -81,11 +230,14 @@ impl Display for ChunkType {
 // Chunk type errors
 #[derive(Debug)]
 pub enum ChunkTypeError {
-    // Chunk has incorrect number of bytes (4 expected)
-    ByteLengthError(usize),
+    // Chunk has incorrect number of bytes (4 expected), with the offending input
+    ByteLengthError(usize, String),
 
     // The input string contains an invalid character at the given index
-    InvalidCharacter,
+    InvalidCharacter(usize),
+
+    // The reserved bit (case of the third character) is not valid
+    ReservedBitInvalid,
 }
 
 impl std::error::Error for ChunkTypeError {}
@@ -93,13 +245,19 @@ impl std::error::Error for ChunkTypeError {}
 impl Display for ChunkTypeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ChunkTypeError::ByteLengthError(actual) => write!(
+            ChunkTypeError::ByteLengthError(actual, input) => write!(
+                f,
+                "chunk type must be exactly 4 ASCII letters, got {} ('{}')",
+                actual,
+                input
+            ),
+            ChunkTypeError::InvalidCharacter(index) => write!(
                 f,
-                "Expected 4 bytes but received {} when creating chunk type",
-                actual
+                "Input contains an invalid character at index {}, only ASCII letters are allowed",
+                index
             ),
-            ChunkTypeError::InvalidCharacter => {
-                write!(f, "Input contains one or more invalid characters")
+            ChunkTypeError::ReservedBitInvalid => {
+                write!(f, "Reserved bit is not valid, the third character must be uppercase")
             }
         }
     }
@@ -119,6 +277,21 @@ mod tests {
         assert_eq!(expected, actual.bytes());
     }
 
+    #[test]
+    pub fn test_chunk_type_bytes_ref() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(chunk.bytes_ref(), &[82, 117, 83, 116]);
+        assert_eq!(*chunk.bytes_ref(), chunk.bytes());
+    }
+
+    #[test]
+    pub fn test_chunk_type_as_ref() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        let bytes: &[u8] = chunk.as_ref();
+        assert_eq!(bytes, b"RuSt");
+        assert_eq!(bytes.len(), 4);
+    }
+
     #[test]
     pub fn test_chunk_type_from_str() {
         let expected = ChunkType::try_from([82, 117, 83, 116]).unwrap();
@@ -189,12 +362,132 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    pub fn test_chunk_type_to_u32() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(chunk.to_u32(), 0x5275_5374);
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_u32() {
+        let chunk = ChunkType::from_u32(0x5275_5374).unwrap();
+        assert_eq!(&chunk.to_string(), "RuSt");
+        assert!(ChunkType::from_u32(0x5275_5331).is_err());
+    }
+
+    #[test]
+    pub fn test_chunk_type_u32_round_trip() {
+        let chunk = ChunkType::from_str("IHDR").unwrap();
+        assert_eq!(ChunkType::from_u32(chunk.to_u32()).unwrap(), chunk);
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_standard() {
+        let chunk = ChunkType::from_str("IHDR").unwrap();
+        assert!(chunk.is_standard());
+        assert_eq!(chunk.standard_description(), Some("Image header"));
+
+        let chunk = ChunkType::from_str("tEXt").unwrap();
+        assert!(chunk.is_standard());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_not_standard() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert!(!chunk.is_standard());
+        assert_eq!(chunk.standard_description(), None);
+    }
+
+    #[test]
+    pub fn test_chunk_type_validate_with_reason() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert!(chunk.validate_with_reason().is_ok());
+
+        let chunk = ChunkType::from_str("Rust").unwrap();
+        assert!(matches!(chunk.validate_with_reason(), Err(ChunkTypeError::ReservedBitInvalid)));
+
+        let chunk = ChunkType::try_from(*b"Ru1t").unwrap();
+        assert!(matches!(chunk.validate_with_reason(), Err(ChunkTypeError::InvalidCharacter(2))));
+        assert!(!chunk.is_valid());
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_str_invalid_character() {
+        // a 4 byte input with a non-letter is a character error, not a length error
+        let err = ChunkType::from_str("Ru1t").unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkTypeError>(), Some(ChunkTypeError::InvalidCharacter(2))));
+        assert_eq!(
+            err.to_string(),
+            "Input contains an invalid character at index 2, only ASCII letters are allowed"
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_byte_length_error() {
+        let err = ChunkType::from_str("abc").unwrap_err();
+        assert_eq!(err.to_string(), "chunk type must be exactly 4 ASCII letters, got 3 ('abc')");
+        assert!(matches!(
+            err.downcast_ref::<ChunkTypeError>(),
+            Some(ChunkTypeError::ByteLengthError(3, input)) if input == "abc"
+        ));
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_slice() {
+        let buffer = b"xxRuStxx";
+        let chunk = ChunkType::try_from(&buffer[2..6]).unwrap();
+        assert_eq!(chunk, ChunkType::from_str("RuSt").unwrap());
+
+        let err = ChunkType::try_from(&buffer[..5]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ChunkTypeError>(),
+            Some(ChunkTypeError::ByteLengthError(5, input)) if input == "xxRuS"
+        ));
+        let err = ChunkType::try_from(&b"Ru1t"[..]).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkTypeError>(), Some(ChunkTypeError::InvalidCharacter(2))));
+    }
+
     #[test]
     pub fn test_chunk_type_string() {
         let chunk = ChunkType::from_str("RuSt").unwrap();
         assert_eq!(&chunk.to_string(), "RuSt");
     }
 
+    #[test]
+    pub fn test_chunk_type_new_private() {
+        let chunk = ChunkType::new_private("NOTES").unwrap();
+        assert_eq!(chunk.to_string(), "noTe");
+        assert!(chunk.is_valid());
+        assert!(!chunk.is_critical());
+        assert!(!chunk.is_public());
+        assert!(chunk.is_safe_to_copy());
+
+        assert_eq!(ChunkType::new_private("ab").unwrap().to_string(), "abXx");
+        assert!(ChunkType::new_private("").is_err());
+        assert!(ChunkType::new_private("no7e").is_err());
+    }
+
+    #[test]
+    pub fn test_chunk_type_property_bits() {
+        let bits = ChunkType::from_str("RuSt").unwrap().property_bits();
+        assert_eq!(bits, PropertyBits { critical: true, public: false, reserved_valid: true, safe_to_copy: true });
+
+        let bits = ChunkType::from_str("bLAH").unwrap().property_bits();
+        assert_eq!(bits, PropertyBits { critical: false, public: true, reserved_valid: true, safe_to_copy: false });
+    }
+
+    #[test]
+    pub fn test_chunk_type_new_ancillary() {
+        let chunk = ChunkType::new_ancillary("NOTe").unwrap();
+        assert_eq!(chunk.to_string(), "nOTe");
+        assert!(!chunk.is_critical());
+        assert!(chunk.is_public());
+
+        assert_eq!(ChunkType::new_ancillary("ruSt").unwrap().to_string(), "ruSt");
+        assert!(ChunkType::new_ancillary("notes").is_err());
+        assert!(ChunkType::new_ancillary("no7e").is_err());
+    }
+
     #[test]
     pub fn test_chunk_type_trait_impls() {
         let chunk_type_1: ChunkType = TryFrom::try_from([82, 117, 83, 116]).unwrap();
@@ -202,4 +495,44 @@ mod tests {
         let _chunk_string = format!("{}", chunk_type_1);
         let _are_chunks_equal = chunk_type_1 == chunk_type_2;
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn test_chunk_type_copy() {
+        let chunk_type_1 = ChunkType::from_str("RuSt").unwrap();
+        let chunk_type_2 = chunk_type_1;
+        assert_eq!(chunk_type_1, chunk_type_2);
+    }
+
+    #[test]
+    pub fn test_chunk_type_ord() {
+        let mut types: Vec<ChunkType> = ["tEXt", "IHDR", "gAMA", "IEND", "IDAT"]
+            .iter()
+            .map(|t| ChunkType::from_str(t).unwrap())
+            .collect();
+        types.sort();
+        let sorted: Vec<String> = types.iter().map(ChunkType::to_string).collect();
+        assert_eq!(sorted, ["IDAT", "IEND", "IHDR", "gAMA", "tEXt"]);
+    }
+
+    #[test]
+    pub fn test_chunk_type_borrow_lookup() {
+        use std::collections::HashMap;
+        use std::collections::hash_map::DefaultHasher;
+
+        let chunk_type: ChunkType = FromStr::from_str("RuSt").unwrap();
+        let mut map = HashMap::new();
+        map.insert(chunk_type, 42);
+        assert_eq!(map.get(&b"RuSt"[..]), Some(&42));
+        assert_eq!(map.get(&b"ruSt"[..]), None);
+
+        let hash = |value: &dyn Fn(&mut DefaultHasher)| {
+            let mut hasher = DefaultHasher::new();
+            value(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(
+            hash(&|h| chunk_type.hash(h)),
+            hash(&|h| Borrow::<[u8]>::borrow(&chunk_type).hash(h))
+        );
+    }
+}
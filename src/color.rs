@@ -0,0 +1,309 @@
+//! Decoders for the color space chunks `gAMA`, `sRGB` and `cHRM`, and for
+//! the `tRNS` transparency chunk
+//! http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.Colour-space-information
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Display;
+
+use crate::Result;
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::ihdr::{ColorType, Ihdr};
+
+// gAMA, cHRM and every point in cHRM are stored as value * 100000
+const SCALE: f64 = 100_000.0;
+
+/// The image gamma stored in a `gAMA` chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gamma(u32);
+
+impl Gamma {
+    /// Parses the 4 data bytes of a `gAMA` chunk.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        Ok(Gamma(read_u32(data, 4)?[0]))
+    }
+
+    /// The gamma as stored, times 100000.
+    pub fn raw(&self) -> u32 {
+        self.0
+    }
+
+    /// The gamma as a number, e.g. 0.45455 for sRGB-like images.
+    pub fn value(&self) -> f64 {
+        f64::from(self.0) / SCALE
+    }
+}
+
+impl Display for Gamma {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gamma {:.5}", self.value())
+    }
+}
+
+/// The rendering intent stored in an `sRGB` chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderingIntent {
+    Perceptual,
+    RelativeColorimetric,
+    Saturation,
+    AbsoluteColorimetric,
+}
+
+impl RenderingIntent {
+    /// Parses the single data byte of an `sRGB` chunk.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        match data {
+            [intent] => RenderingIntent::try_from(*intent),
+            _ => Err(Box::from(ColorChunkError::InvalidLength(1, data.len()))),
+        }
+    }
+}
+
+impl TryFrom<u8> for RenderingIntent {
+    type Error = crate::Error;
+
+    fn try_from(intent: u8) -> Result<Self> {
+        match intent {
+            0 => Ok(RenderingIntent::Perceptual),
+            1 => Ok(RenderingIntent::RelativeColorimetric),
+            2 => Ok(RenderingIntent::Saturation),
+            3 => Ok(RenderingIntent::AbsoluteColorimetric),
+            _ => Err(Box::from(ColorChunkError::UnknownRenderingIntent(intent))),
+        }
+    }
+}
+
+impl Display for RenderingIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RenderingIntent::Perceptual => "perceptual",
+            RenderingIntent::RelativeColorimetric => "relative colorimetric",
+            RenderingIntent::Saturation => "saturation",
+            RenderingIntent::AbsoluteColorimetric => "absolute colorimetric",
+        };
+        write!(f, "sRGB, {} rendering intent", name)
+    }
+}
+
+/// The white point and primaries stored in a `cHRM` chunk, as (x, y) pairs
+/// times 100000.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chromaticities {
+    pub white_point: (u32, u32),
+    pub red: (u32, u32),
+    pub green: (u32, u32),
+    pub blue: (u32, u32),
+}
+
+impl Chromaticities {
+    /// Parses the 32 data bytes of a `cHRM` chunk.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let values = read_u32(data, 32)?;
+        Ok(Chromaticities {
+            white_point: (values[0], values[1]),
+            red: (values[2], values[3]),
+            green: (values[4], values[5]),
+            blue: (values[6], values[7]),
+        })
+    }
+}
+
+impl Display for Chromaticities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let point = |(x, y): (u32, u32)| format!("({:.5}, {:.5})", f64::from(x) / SCALE, f64::from(y) / SCALE);
+        write!(
+            f,
+            "white {}, red {}, green {}, blue {}",
+            point(self.white_point),
+            point(self.red),
+            point(self.green),
+            point(self.blue)
+        )
+    }
+}
+
+/// The transparency stored in a `tRNS` chunk, which depends on the color type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transparency {
+    /// Alpha for the first palette entries, in palette order
+    Palette(Vec<u8>),
+    /// The gray level that is fully transparent
+    Gray(u16),
+    /// The (red, green, blue) color that is fully transparent
+    Rgb(u16, u16, u16),
+}
+
+impl Display for Transparency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transparency::Palette(alpha) => write!(f, "alpha for {} palette entries", alpha.len()),
+            Transparency::Gray(gray) => write!(f, "transparent gray {}", gray),
+            Transparency::Rgb(red, green, blue) => write!(f, "transparent color ({}, {}, {})", red, green, blue),
+        }
+    }
+}
+
+/// Parses a `tRNS` chunk for an image with the given header. Errors if the
+/// chunk isn't `tRNS`, if the color type already has an alpha channel, or if
+/// the length doesn't fit the color type.
+pub fn parse_trns(chunk: &Chunk, ihdr: &Ihdr) -> Result<Transparency> {
+    if chunk.chunk_type().bytes() != *b"tRNS" {
+        return Err(Box::from(ColorChunkError::UnexpectedChunkType(*chunk.chunk_type())));
+    }
+    let data = chunk.data();
+    let sample = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+    match ihdr.color_type {
+        ColorType::Indexed if data.len() > 256 => Err(Box::from(ColorChunkError::TooManyPaletteEntries(data.len()))),
+        ColorType::Indexed => Ok(Transparency::Palette(data.to_vec())),
+        ColorType::Grayscale if data.len() != 2 => Err(Box::from(ColorChunkError::InvalidLength(2, data.len()))),
+        ColorType::Grayscale => Ok(Transparency::Gray(sample(0))),
+        ColorType::Rgb if data.len() != 6 => Err(Box::from(ColorChunkError::InvalidLength(6, data.len()))),
+        ColorType::Rgb => Ok(Transparency::Rgb(sample(0), sample(2), sample(4))),
+        ColorType::GrayscaleAlpha | ColorType::Rgba => {
+            Err(Box::from(ColorChunkError::TransparencyNotAllowed(ihdr.color_type)))
+        }
+    }
+}
+
+/// Decodes a `gAMA`, `sRGB` or `cHRM` chunk into a readable description.
+/// Returns `None` for any other chunk type, or if the data is malformed.
+pub fn describe(chunk: &Chunk) -> Option<String> {
+    let data = chunk.data();
+    match chunk.chunk_type().bytes() {
+        [b'g', b'A', b'M', b'A'] => Gamma::parse(data).ok().map(|g| g.to_string()),
+        [b's', b'R', b'G', b'B'] => RenderingIntent::parse(data).ok().map(|i| i.to_string()),
+        [b'c', b'H', b'R', b'M'] => Chromaticities::parse(data).ok().map(|c| c.to_string()),
+        _ => None,
+    }
+}
+
+// Reads big-endian u32s out of data that must be exactly `length` bytes long
+fn read_u32(data: &[u8], length: usize) -> Result<Vec<u32>> {
+    if data.len() != length {
+        return Err(Box::from(ColorChunkError::InvalidLength(length, data.len())));
+    }
+    Ok(data
+        .chunks_exact(4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+// Color Chunk Errors
+#[derive(Debug)]
+pub enum ColorChunkError {
+    // Chunk data has the wrong length (expected, actual)
+    InvalidLength(usize, usize),
+
+    // sRGB rendering intent is not one of the four defined values
+    UnknownRenderingIntent(u8),
+
+    // Chunk is not of the type the parser expects
+    UnexpectedChunkType(ChunkType),
+
+    // tRNS holds alpha for more than the 256 possible palette entries
+    TooManyPaletteEntries(usize),
+
+    // tRNS is not allowed for color types with an alpha channel
+    TransparencyNotAllowed(ColorType),
+}
+
+impl std::error::Error for ColorChunkError {}
+
+impl Display for ColorChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorChunkError::InvalidLength(expected, actual) => {
+                write!(f, "Color chunk data must be {} bytes, got {}", expected, actual)
+            },
+            ColorChunkError::UnknownRenderingIntent(intent) => {
+                write!(f, "Unknown sRGB rendering intent {}", intent)
+            },
+            ColorChunkError::UnexpectedChunkType(chunk_type) => {
+                write!(f, "Unexpected chunk type {}", chunk_type)
+            },
+            ColorChunkError::TooManyPaletteEntries(entries) => {
+                write!(f, "tRNS holds alpha for {} palette entries, at most 256 are allowed", entries)
+            },
+            ColorChunkError::TransparencyNotAllowed(color_type) => {
+                write!(f, "tRNS is not allowed for {} images, they already have an alpha channel", color_type)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+    }
+
+    #[test]
+    fn test_gamma() {
+        let gamma = Gamma::parse(&45455u32.to_be_bytes()).unwrap();
+        assert_eq!(gamma.raw(), 45455);
+        assert_eq!(gamma.to_string(), "gamma 0.45455");
+        assert!(Gamma::parse(&[0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_rendering_intent() {
+        assert_eq!(RenderingIntent::parse(&[0]).unwrap(), RenderingIntent::Perceptual);
+        assert_eq!(RenderingIntent::parse(&[3]).unwrap(), RenderingIntent::AbsoluteColorimetric);
+        assert!(RenderingIntent::parse(&[4]).is_err());
+        assert!(RenderingIntent::parse(&[0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_chromaticities() {
+        let data: Vec<u8> = [31270u32, 32900, 64000, 33000, 30000, 60000, 15000, 6000]
+            .iter()
+            .flat_map(|v| v.to_be_bytes().to_vec())
+            .collect();
+        let chrm = Chromaticities::parse(&data).unwrap();
+        assert_eq!(chrm.white_point, (31270, 32900));
+        assert_eq!(chrm.blue, (15000, 6000));
+        assert!(chrm.to_string().starts_with("white (0.31270, 0.32900), red (0.64000, 0.33000)"));
+        assert!(Chromaticities::parse(&data[..28]).is_err());
+    }
+
+    #[test]
+    fn test_parse_trns() {
+        let ihdr = |color_type| Ihdr {
+            width: 1,
+            height: 1,
+            bit_depth: 8,
+            color_type,
+            compression_method: 0,
+            filter_method: 0,
+            interlace_method: 0,
+        };
+        let trns = |data: Vec<u8>| chunk("tRNS", data);
+
+        assert_eq!(
+            parse_trns(&trns(vec![0, 128]), &ihdr(ColorType::Indexed)).unwrap(),
+            Transparency::Palette(vec![0, 128])
+        );
+        assert!(parse_trns(&trns(vec![0; 257]), &ihdr(ColorType::Indexed)).is_err());
+        assert_eq!(parse_trns(&trns(vec![1, 2]), &ihdr(ColorType::Grayscale)).unwrap(), Transparency::Gray(258));
+        assert!(parse_trns(&trns(vec![1, 2, 3]), &ihdr(ColorType::Grayscale)).is_err());
+        assert_eq!(
+            parse_trns(&trns(vec![0, 1, 0, 2, 0, 3]), &ihdr(ColorType::Rgb)).unwrap(),
+            Transparency::Rgb(1, 2, 3)
+        );
+        assert!(parse_trns(&trns(vec![0, 1]), &ihdr(ColorType::Rgb)).is_err());
+        assert!(parse_trns(&trns(vec![]), &ihdr(ColorType::Rgba)).is_err());
+        assert!(parse_trns(&chunk("gAMA", vec![0, 1]), &ihdr(ColorType::Grayscale)).is_err());
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(&chunk("sRGB", vec![0])).unwrap(), "sRGB, perceptual rendering intent");
+        assert_eq!(describe(&chunk("gAMA", vec![0, 0, 0])), None);
+        assert_eq!(describe(&chunk("RuSt", vec![0, 0, 0, 1])), None);
+    }
+}
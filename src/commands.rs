@@ -0,0 +1,951 @@
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use pngme::chunk::{Chunk, ChunkError, ParseOptions};
+use pngme::chunk_type::ChunkType;
+use pngme::in_memory::{self, EncodeOptions, Placement};
+use pngme::png::{Png, PngError};
+use pngme::Result;
+
+use crate::args::{
+    BatchEncodeArgs, ChunkInfoArgs, DecodeArgs, DumpArgs, EncodeArgs, GenTypeArgs, GlobalArgs,
+    IsPngArgs, ParseArgs, PrintArgs, RemoveArgs, RenameTypeArgs, SetTextArgs, StripArgs,
+    ValidateArgs,
+};
+use crate::hex;
+use crate::json::{self, JsonObject};
+use crate::style::Style;
+
+fn parse_options(parse: &ParseArgs) -> ParseOptions {
+    if parse.strict {
+        ParseOptions::strict()
+    } else {
+        ParseOptions::lenient()
+    }
+}
+
+// Lenient parse options that skip CRC checks, with a warning so it isn't silent
+fn ignore_crc_options() -> ParseOptions {
+    eprintln!("warning: ignoring CRCs, chunk data may be corrupt");
+    ParseOptions { verify_crc: false, ..ParseOptions::lenient() }
+}
+
+fn read_png(path: &Path, parse: &ParseArgs) -> Result<Png> {
+    read_png_with(path, &parse_options(parse))
+}
+
+// Reads a file, checking its signature first so that files which aren't PNGs
+// are reported as such whatever their name, instead of as a chunk parse error
+fn read_png_bytes(path: &Path) -> Result<Vec<u8>> {
+    let bytes = fs::read(path)?;
+    if !Png::starts_with_signature(bytes.as_slice())? {
+        return Err(Box::from(PngError::InvaldPngHeader));
+    }
+    Ok(bytes)
+}
+
+fn read_png_with(path: &Path, options: &ParseOptions) -> Result<Png> {
+    let bytes = read_png_bytes(path)?;
+    Png::parse(&bytes, options)
+}
+
+// The bytes to store for `message`, honouring --hex, --base64 and --encoding
+fn decode_message_arg(args: &EncodeArgs, message: &str) -> Result<Vec<u8>> {
+    #[cfg(feature = "base64")]
+    {
+        if args.base64 {
+            use base64::Engine;
+            return Ok(base64::engine::general_purpose::STANDARD.decode(message)?);
+        }
+    }
+
+    if args.hex {
+        hex::decode(message)
+    } else {
+        args.encoding.encode(message)
+    }
+}
+
+// Writes `prompt` and reads one line of input without its line ending
+fn prompt_line<R: BufRead, W: Write>(input: &mut R, output: &mut W, prompt: &str) -> Result<String> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err(Box::from(io::Error::new(io::ErrorKind::UnexpectedEof, "no input")));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+// Prompts until a valid chunk type is entered
+fn prompt_chunk_type<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> Result<String> {
+    loop {
+        let line = prompt_line(input, output, "Chunk type: ")?;
+        match ChunkType::from_str(line.trim()) {
+            Ok(chunk_type) => match chunk_type.validate_with_reason() {
+                Ok(()) => return Ok(chunk_type.to_string()),
+                Err(e) => writeln!(output, "{}", e)?,
+            },
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+fn chunk_json(chunk: &Chunk) -> JsonObject {
+    JsonObject::new()
+        .string("chunk_type", &chunk.chunk_type().to_string())
+        .number("length", chunk.length() as u64)
+        .number("crc", chunk.crc())
+}
+
+// The Display of a chunk with its type colored and its data decoded where
+// the color module knows how, plus its data entropy before the CRC line when
+// `entropy` is set
+fn write_styled_chunk(out: &mut String, chunk: &Chunk, style: &Style, entropy: bool) -> std::fmt::Result {
+    let chunk_type = style.chunk_type(&chunk.chunk_type().to_string());
+    let description = pngme::color::describe(chunk);
+    let data = description.as_ref().map(|d| d as &dyn std::fmt::Display);
+    if entropy {
+        let extras = [("Entropy", format!("{:.3} bits/byte", chunk.data_entropy()))];
+        chunk.write_with(out, &chunk_type, data, &extras)
+    } else {
+        chunk.write_with(out, &chunk_type, data, &[])
+    }
+}
+
+// Chunk count and serialized size of a Png, for --summary
+fn png_state(png: &Png) -> (usize, usize) {
+    (png.chunks().len(), png.total_size())
+}
+
+// Describes a change for --summary on stderr, so piped stdout stays clean
+fn print_summary(
+    action: &str,
+    chunk_type: &str,
+    chunks: usize,
+    data_length: usize,
+    before: (usize, usize),
+    after: (usize, usize),
+) {
+    eprintln!("{} {} {} chunk(s), {} bytes of data", action, chunks, chunk_type, data_length);
+    eprintln!("chunks: {} -> {}", before.0, after.0);
+    eprintln!("size: {} -> {} bytes", before.1, after.1);
+}
+
+/// Encodes a message into a PNG file and saves the result
+pub fn encode(args: EncodeArgs, global: &GlobalArgs) -> Result<()> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut prompts = io::stderr();
+
+    let chunk_type = match &args.chunk_type {
+        Some(chunk_type) => chunk_type.clone(),
+        None => prompt_chunk_type(&mut input, &mut prompts)?,
+    };
+    let chunk_type = if args.normalize {
+        let normalized = ChunkType::new_private(&chunk_type)?.to_string();
+        if normalized != chunk_type {
+            eprintln!(
+                "warning: using chunk type {} instead of {} (ancillary, private, safe to copy)",
+                normalized, chunk_type
+            );
+        }
+        normalized
+    } else {
+        chunk_type
+    };
+    let message = match &args.message {
+        Some(message) => message.clone(),
+        None => prompt_line(&mut input, &mut prompts, "Message: ")?,
+    };
+    let message = decode_message_arg(&args, &message)?;
+
+    if args.interactive {
+        let chunk = Chunk::new(ChunkType::from_str(&chunk_type)?, message.clone());
+        eprintln!("{}", chunk.summary_line());
+    }
+
+    let options = EncodeOptions {
+        parse: parse_options(&args.parse),
+        validate: args.validate && !args.force,
+        max_message_size: args.max_message_size,
+        max_output_size: args.max_output_size,
+        version_chunk: args.version_chunk,
+        placement: match (&args.after_type, args.insert_at) {
+            (Some(after_type), _) => Placement::AfterType(after_type.clone()),
+            (None, Some(index)) => Placement::At(index),
+            (None, None) => Placement::BeforeIend,
+        },
+    };
+
+    let bytes = read_png_bytes(&args.file_path)?;
+    let png = in_memory::encode_png_with(&bytes, &chunk_type, &message, &options)?;
+
+    // --force only skips the validation in encode_png_with, it still reports
+    if args.force {
+        if let Err(e) = png.validate() {
+            eprintln!("warning: writing invalid PNG: {}", e);
+        }
+    }
+
+    // counted before anything is written, so --summary can't fail on a changed file
+    let states = if args.summary {
+        Some((png_state(&Png::parse(&bytes, &options.parse)?), png_state(&png)))
+    } else {
+        None
+    };
+
+    if args.backup {
+        fs::write(backup_path(&args.file_path), &bytes)?;
+    }
+    let output = args.output.as_deref().unwrap_or(&args.file_path);
+    png.write_path(output)?;
+
+    if let Some((before, after)) = states {
+        print_summary("encoded", &chunk_type, 1, message.len(), before, after);
+    }
+
+    if global.json {
+        let summary = JsonObject::new()
+            .string("chunk_type", &chunk_type)
+            .number("length", message.len() as u64)
+            .string("file", &output.display().to_string());
+        println!("{}", summary.finish());
+    }
+    Ok(())
+}
+
+// Encodes the message into one file in place. The error is turned into text
+// so results can be sent back from worker threads.
+fn encode_file(
+    path: &Path,
+    chunk_type: &str,
+    message: &[u8],
+    options: &EncodeOptions,
+) -> std::result::Result<(), String> {
+    let encode = || -> Result<()> {
+        let bytes = read_png_bytes(path)?;
+        in_memory::encode_png_with(&bytes, chunk_type, message, options)?.write_path(path)
+    };
+    encode().map_err(|e| e.to_string())
+}
+
+// Results are returned in the same order as `args.files`
+#[cfg(feature = "parallel")]
+fn encode_files(
+    args: &BatchEncodeArgs,
+    message: &[u8],
+    options: &EncodeOptions,
+) -> Result<Vec<std::result::Result<(), String>>> {
+    use rayon::prelude::*;
+
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(threads) = args.threads {
+        pool = pool.num_threads(threads);
+    }
+    let pool = pool.build()?;
+    Ok(pool.install(|| {
+        args.files
+            .par_iter()
+            .map(|path| encode_file(path, &args.chunk_type, message, options))
+            .collect()
+    }))
+}
+
+#[cfg(not(feature = "parallel"))]
+fn encode_files(
+    args: &BatchEncodeArgs,
+    message: &[u8],
+    options: &EncodeOptions,
+) -> Result<Vec<std::result::Result<(), String>>> {
+    Ok(args.files
+        .iter()
+        .map(|path| encode_file(path, &args.chunk_type, message, options))
+        .collect())
+}
+
+/// Encodes one message into every file, reporting each file's result in
+/// argument order and exiting with a non-zero status if any of them failed
+pub fn batch_encode(args: BatchEncodeArgs, global: &GlobalArgs) -> Result<()> {
+    ChunkType::from_str(&args.chunk_type)?;
+    let message = args.encoding.encode(&args.message)?;
+    let options = EncodeOptions {
+        parse: parse_options(&args.parse),
+        ..EncodeOptions::default()
+    };
+
+    let results = encode_files(&args, &message, &options)?;
+    let failed = results.iter().filter(|r| r.is_err()).count();
+
+    if global.json {
+        let files = args.files.iter().zip(&results).map(|(path, result)| {
+            let object = JsonObject::new().string("file", &path.display().to_string());
+            match result {
+                Ok(()) => object.boolean("ok", true),
+                Err(e) => object.boolean("ok", false).string("error", e),
+            }
+            .finish()
+        });
+        let summary = JsonObject::new()
+            .raw("files", json::array(files))
+            .number("failed", failed as u64);
+        println!("{}", summary.finish());
+    } else {
+        for (path, result) in args.files.iter().zip(&results) {
+            match result {
+                Ok(()) => println!("{}: ok", path.display()),
+                Err(e) => println!("{}: {}", path.display(), e),
+            }
+        }
+        println!("Encoded {} of {} files", results.len() - failed, results.len());
+    }
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Searches for a message hidden in a PNG file and prints the message if one is found
+pub fn decode(args: DecodeArgs, global: &GlobalArgs) -> Result<()> {
+    let bytes = read_png_bytes(&args.file_path)?;
+    let options = if args.ignore_crc {
+        ignore_crc_options()
+    } else {
+        parse_options(&args.parse)
+    };
+    let data = in_memory::decode_in_memory_with(&bytes, &args.chunk_type, &options)?
+        .ok_or(PngError::ChunkTypeNotFound)?;
+    let message = if args.hex {
+        hex::encode(&data)
+    } else {
+        args.encoding.decode(&data)?
+    };
+
+    if global.json {
+        let summary = JsonObject::new()
+            .string("chunk_type", &args.chunk_type)
+            .number("length", data.len() as u64)
+            .string("message", &message);
+        println!("{}", summary.finish());
+    } else {
+        println!("{}", message);
+    }
+    Ok(())
+}
+
+/// Removes a chunk from a PNG file and saves the result
+pub fn remove(args: RemoveArgs, global: &GlobalArgs) -> Result<()> {
+    if ChunkType::from_str(&args.chunk_type)?.is_critical() && !args.force {
+        return Err(Box::from(RemoveError::CriticalChunk(args.chunk_type)));
+    }
+
+    let mut png = read_png(&args.file_path, &args.parse)?;
+    let before = png_state(&png);
+    let removed_chunks = if args.all {
+        png.remove_all_chunks(&args.chunk_type)?
+    } else {
+        vec![png.remove_chunk(&args.chunk_type)?]
+    };
+    let removed = removed_chunks.len();
+    let remaining = png.count_of_type(&args.chunk_type);
+    png.write_path(&args.file_path)?;
+
+    if args.summary {
+        let data_length = removed_chunks.iter().map(Chunk::length).sum();
+        print_summary("removed", &args.chunk_type, removed, data_length, before, png_state(&png));
+    }
+
+    if global.json {
+        let summary = JsonObject::new()
+            .string("chunk_type", &args.chunk_type)
+            .number("removed", removed as u64)
+            .number("remaining", remaining as u64);
+        println!("{}", summary.finish());
+    } else if args.count || args.all {
+        println!("removed {}, {} remaining of type {}.", removed, remaining, args.chunk_type);
+    }
+    Ok(())
+}
+
+/// Prints all of the chunks in a PNG file
+pub fn print_chunks(args: PrintArgs, global: &GlobalArgs) -> Result<()> {
+    let png = if args.ignore_crc {
+        read_png_with(&args.file_path, &ignore_crc_options())?
+    } else {
+        read_png(&args.file_path, &args.parse)?
+    };
+    let style = Style::from_args(global);
+
+    if args.type_only {
+        let types = png.chunk_types_str();
+        if global.json {
+            let types = json::array(types.iter().map(|t| json::escape(t)));
+            println!("{}", JsonObject::new().raw("chunk_types", types).finish());
+        } else if args.inline {
+            let types: Vec<String> = types.iter().map(|t| style.chunk_type(t)).collect();
+            println!("{}", types.join(","));
+        } else {
+            for chunk_type in types {
+                println!("{}", style.chunk_type(&chunk_type));
+            }
+        }
+    } else if global.json {
+        let chunks = json::array(png.iter_with_offsets().map(|(offset, c)| {
+            #[cfg(feature = "base64")]
+            let json = chunk_json(c).string("data", &c.data_base64());
+            #[cfg(not(feature = "base64"))]
+            let json = chunk_json(c);
+            let json = if args.offset { json.number("offset", offset as u64) } else { json };
+            let json = if args.entropy { json.raw("entropy", format!("{:.3}", c.data_entropy())) } else { json };
+            json.finish()
+        }));
+        println!("{}", JsonObject::new().raw("chunks", chunks).finish());
+    } else if args.compact {
+        for (offset, chunk) in png.iter_with_offsets() {
+            let chunk_type = style.chunk_type(&chunk.chunk_type().to_string());
+            let mut line = chunk.summary_line_with(&chunk_type);
+            if args.entropy {
+                line.push_str(&format!(" | {:.3}", chunk.data_entropy()));
+            }
+            if args.offset {
+                println!("{:>10} | {}", offset, line);
+            } else {
+                println!("{}", line);
+            }
+        }
+    } else if args.offset {
+        let mut text = String::from("Png {\n");
+        for (offset, chunk) in png.iter_with_offsets() {
+            text.push_str(&format!("Offset: {}\n", offset));
+            write_styled_chunk(&mut text, chunk, &style, args.entropy)?;
+        }
+        text.push_str("}\n");
+        print!("{}", text);
+    } else {
+        let mut text = String::new();
+        png.write_with(&mut text, |out, chunk| write_styled_chunk(out, chunk, &style, args.entropy))?;
+        print!("{}", text);
+    }
+    Ok(())
+}
+
+/// Prints a hex dump of the data of the `--index`th chunk of a type
+pub fn dump(args: DumpArgs, global: &GlobalArgs) -> Result<()> {
+    let png = read_png(&args.file_path, &args.parse)?;
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    let matching: Vec<&Chunk> = png.chunks()
+        .iter()
+        .filter(|c| *c.chunk_type() == chunk_type)
+        .collect();
+    if matching.is_empty() {
+        return Err(Box::from(PngError::ChunkTypeNotFound));
+    }
+    let chunk = matching
+        .get(args.index)
+        .ok_or(PngError::IndexOutOfBounds(args.index, matching.len()))?;
+
+    if global.json {
+        let summary = chunk_json(chunk)
+            .number("index", args.index as u64)
+            .string("data", &hex::encode(chunk.data()));
+        println!("{}", summary.finish());
+    } else {
+        print!("{}", hex::dump(chunk.data()));
+    }
+    Ok(())
+}
+
+// Reads one serialized chunk from a file or stdin and parses it with `options`,
+// refusing input that is actually a whole PNG. Stdin is read no further than
+// the largest chunk `options` allows.
+fn read_raw_chunk(path: Option<&Path>, options: &ParseOptions) -> Result<Chunk> {
+    let bytes = match path {
+        Some(path) => fs::read(path)?,
+        None => {
+            let limit = options
+                .max_data_length
+                .map_or(u64::MAX, |max_data| (max_data + Chunk::MINIUMUM_LENGTH) as u64);
+            let mut bytes = Vec::new();
+            io::stdin().lock().take(limit).read_to_end(&mut bytes)?;
+            bytes
+        }
+    };
+    if Png::starts_with_signature(bytes.as_slice())? {
+        return Err(Box::from(ChunkInfoError::PngInput));
+    }
+    Chunk::parse(&bytes, options)
+}
+
+/// Describes every chunk of a PNG, or the single serialized chunk given with --raw
+pub fn chunk_info(args: ChunkInfoArgs, global: &GlobalArgs) -> Result<()> {
+    let chunks = if args.raw {
+        vec![read_raw_chunk(args.file_path.as_deref(), &parse_options(&args.parse))?]
+    } else {
+        let png = match &args.file_path {
+            Some(path) => read_png(path, &args.parse)?,
+            None => {
+                let mut bytes = Vec::new();
+                io::stdin().read_to_end(&mut bytes)?;
+                Png::parse(&bytes, &parse_options(&args.parse))?
+            }
+        };
+        png.take_chunks()
+    };
+
+    if global.json {
+        let chunks = json::array(chunks.iter().map(|chunk| {
+            let bits = chunk.chunk_type().property_bits();
+            chunk_json(chunk)
+                .boolean("critical", bits.critical)
+                .boolean("public", bits.public)
+                .boolean("safe_to_copy", bits.safe_to_copy)
+                .finish()
+        }));
+        println!("{}", JsonObject::new().raw("chunks", chunks).finish());
+    } else {
+        for (index, chunk) in chunks.iter().enumerate() {
+            if index > 0 {
+                println!();
+            }
+            let bits = chunk.chunk_type().property_bits();
+            println!("type: {}", chunk.chunk_type());
+            println!("length: {}", chunk.length());
+            println!("crc: {}", chunk.crc());
+            println!("critical: {}", bits.critical);
+            println!("public: {}", bits.public);
+            println!("safe to copy: {}", bits.safe_to_copy);
+            if let Some(description) = pngme::color::describe(chunk) {
+                println!("data: {}", description);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reports structural problems and every CRC mismatch of a PNG file, exiting
+/// with a non-zero status if there are any
+pub fn validate(args: ValidateArgs, global: &GlobalArgs) -> Result<()> {
+    let options = ParseOptions { verify_crc: false, ..ParseOptions::lenient() };
+    let png = read_png_with(&args.file_path, &options)?;
+    let structure = png.validate();
+    let crc_errors = png.validate_crcs();
+    let valid = structure.is_ok() && crc_errors.is_empty();
+
+    if global.json {
+        let crcs = json::array(crc_errors.iter().map(|(index, err)| {
+            let object = JsonObject::new()
+                .number("index", *index as u64)
+                .string("chunk_type", &png.chunks()[*index].chunk_type().to_string());
+            match err {
+                ChunkError::InvalidCrc(expected, actual) => {
+                    object.number("expected", *expected).number("actual", *actual)
+                }
+                _ => object,
+            }
+            .finish()
+        }));
+        let mut summary = JsonObject::new().boolean("valid", valid);
+        if let Err(e) = &structure {
+            summary = summary.string("structure_error", &e.to_string());
+        }
+        println!("{}", summary.raw("crc_errors", crcs).finish());
+    } else {
+        let style = Style::from_args(global);
+        match &structure {
+            Ok(()) => println!("structure: {}", style.ok("ok")),
+            Err(e) => println!("structure: {}", style.error(&e.to_string())),
+        }
+        for (index, err) in &crc_errors {
+            let chunk_type = png.chunks()[*index].chunk_type().to_string();
+            println!("chunk {} ({}): {}", index, style.chunk_type(&chunk_type), style.error(&err.to_string()));
+        }
+        if crc_errors.is_empty() {
+            println!("crcs: {}", style.ok("ok"));
+        }
+    }
+
+    if !valid {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Removes every ancillary chunk from a PNG file and saves the result
+pub fn strip(args: StripArgs, global: &GlobalArgs) -> Result<()> {
+    let mut png = read_png(&args.file_path, &args.parse)?;
+    let removed = png.strip_metadata();
+    png.validate()?;
+    png.write_path(&args.file_path)?;
+
+    if global.json {
+        println!("{}", JsonObject::new().number("removed", removed as u64).finish());
+    } else {
+        println!("Removed {} ancillary chunks", removed);
+    }
+    Ok(())
+}
+
+/// Changes the type of every chunk of type --from to --to
+pub fn rename_type(args: RenameTypeArgs, global: &GlobalArgs) -> Result<()> {
+    let mut png = read_png(&args.file_path, &args.parse)?;
+    let renamed = png.rename_chunk_type(&args.from, &args.to)?;
+    png.write_path(&args.file_path)?;
+
+    if global.json {
+        let summary = JsonObject::new()
+            .string("from", &args.from)
+            .string("to", &args.to)
+            .number("renamed", renamed as u64);
+        println!("{}", summary.finish());
+    } else {
+        println!("Renamed {} {} chunks to {}", renamed, args.from, args.to);
+    }
+    Ok(())
+}
+
+/// Adds a tEXt chunk to a PNG file, or replaces one with the same keyword with --replace
+pub fn set_text(args: SetTextArgs, global: &GlobalArgs) -> Result<()> {
+    let mut png = read_png(&args.file_path, &args.parse)?;
+    let replaced = if args.replace {
+        png.set_text(&args.keyword, &args.text)?.is_some()
+    } else {
+        png.insert_text(&args.keyword, &args.text)?;
+        false
+    };
+    png.write_path(&args.file_path)?;
+
+    if global.json {
+        let summary = JsonObject::new()
+            .string("keyword", &args.keyword)
+            .boolean("replaced", replaced);
+        println!("{}", summary.finish());
+    }
+    Ok(())
+}
+
+/// Checks only the signature of a file, exiting with a non-zero status if it is not a PNG
+pub fn is_png(args: IsPngArgs, global: &GlobalArgs) -> Result<()> {
+    let file = fs::File::open(&args.file_path)?;
+    let is_png = Png::starts_with_signature(file)?;
+
+    if global.json {
+        println!("{}", JsonObject::new().boolean("is_png", is_png).finish());
+    } else if args.verbose {
+        let verdict = if is_png { "is a PNG" } else { "is not a PNG" };
+        println!("{} {}", args.file_path.display(), verdict);
+    }
+
+    if !is_png {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Prints a private, ancillary, safe-to-copy chunk type built from a word
+pub fn gen_type(args: GenTypeArgs, global: &GlobalArgs) -> Result<()> {
+    let chunk_type = ChunkType::new_private(&args.name)?;
+
+    if global.json {
+        println!("{}", JsonObject::new().string("chunk_type", &chunk_type.to_string()).finish());
+    } else {
+        println!("{}", chunk_type);
+        for line in explain_casing(&args.name, chunk_type) {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+// How `new_private` turned `name` into `chunk_type`: whether the name was cut
+// or padded, then one line per letter with its input and output case
+fn explain_casing(name: &str, chunk_type: ChunkType) -> Vec<String> {
+    let case = |letter: u8| if letter.is_ascii_uppercase() { "uppercase" } else { "lowercase" };
+    let mut lines = Vec::new();
+    if name.len() > 4 {
+        lines.push(format!("  used the first 4 letters of \"{}\"", name));
+    } else if name.len() < 4 {
+        lines.push(format!("  padded \"{}\" with x to 4 letters", name));
+    }
+    let meanings = ["ancillary", "private", "reserved bit", "safe to copy"];
+    for (index, (&output, meaning)) in chunk_type.bytes().iter().zip(meanings).enumerate() {
+        let line = match name.as_bytes().get(index) {
+            Some(&input) if case(input) == case(output) => {
+                format!("  {} -> {}: {}, {}", input as char, output as char, case(output), meaning)
+            }
+            Some(&input) => format!(
+                "  {} -> {}: {} (was {}), {}",
+                input as char, output as char, case(output), case(input), meaning
+            ),
+            None => format!("  {}: {}, {} (padding)", output as char, case(output), meaning),
+        };
+        lines.push(line);
+    }
+    lines
+}
+
+/// Prints every standard chunk type with whether it is critical and what it holds
+pub fn list_types(global: &GlobalArgs) -> Result<()> {
+    let types = ChunkType::reserved_standard_types()
+        .iter()
+        .map(|&(name, description)| Ok((ChunkType::from_str(name)?, description)))
+        .collect::<Result<Vec<_>>>()?;
+
+    if global.json {
+        let types = json::array(types.iter().map(|(chunk_type, description)| {
+            JsonObject::new()
+                .string("chunk_type", &chunk_type.to_string())
+                .boolean("critical", chunk_type.is_critical())
+                .string("description", description)
+                .finish()
+        }));
+        println!("{}", JsonObject::new().raw("chunk_types", types).finish());
+    } else {
+        let style = Style::from_args(global);
+        println!("TYPE | KIND      | DESCRIPTION");
+        for (chunk_type, description) in types {
+            let kind = if chunk_type.is_critical() { "CRITICAL" } else { "ancillary" };
+            println!("{} | {:<9} | {}", style.chunk_type(&chunk_type.to_string()), kind, description);
+        }
+    }
+    Ok(())
+}
+
+// Remove command errors
+#[derive(Debug)]
+pub enum RemoveError {
+    // Refused to remove a critical chunk without --force
+    CriticalChunk(String),
+}
+
+impl std::error::Error for RemoveError {}
+
+impl std::fmt::Display for RemoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoveError::CriticalChunk(chunk_type) => write!(
+                f,
+                "Refusing to remove critical chunk {}, the image may no longer display without it. Pass --force to remove it anyway",
+                chunk_type
+            ),
+        }
+    }
+}
+
+// Chunk info command errors
+#[derive(Debug)]
+pub enum ChunkInfoError {
+    // --raw was given a whole PNG rather than a single chunk
+    PngInput,
+}
+
+impl std::error::Error for ChunkInfoError {}
+
+impl std::fmt::Display for ChunkInfoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkInfoError::PngInput => write!(
+                f,
+                "Input starts with the PNG signature, run without --raw to read it as a PNG"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pngme::chunk_type::ChunkTypeError;
+    use std::convert::TryFrom;
+
+    fn global_args() -> GlobalArgs {
+        GlobalArgs {
+            json: false,
+            #[cfg(feature = "color")]
+            color: crate::style::ColorChoice::Never,
+        }
+    }
+
+    fn png_of_types(types: &[&str]) -> Png {
+        let chunks = types
+            .iter()
+            .map(|t| Chunk::new(ChunkType::from_str(t).unwrap(), vec![]))
+            .collect();
+        Png::from_chunks(chunks)
+    }
+
+    #[test]
+    fn test_prompt_line() {
+        let mut input = "hello world\r\n".as_bytes();
+        let mut output = Vec::new();
+        let line = prompt_line(&mut input, &mut output, "Message: ").unwrap();
+        assert_eq!(line, "hello world");
+        assert_eq!(output, b"Message: ");
+    }
+
+    #[test]
+    fn test_prompt_chunk_type_retries() {
+        let mut input = "ru5t\nRust\nruSt\n".as_bytes();
+        let mut output = Vec::new();
+        let chunk_type = prompt_chunk_type(&mut input, &mut output).unwrap();
+        assert_eq!(chunk_type, "ruSt");
+        assert_eq!(String::from_utf8(output).unwrap().matches("Chunk type: ").count(), 3);
+    }
+
+    #[test]
+    fn test_prompt_chunk_type_eof() {
+        let mut input = "".as_bytes();
+        assert!(prompt_chunk_type(&mut input, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(backup_path(Path::new("dir/image.png")), PathBuf::from("dir/image.png.bak"));
+    }
+
+    #[test]
+    fn test_read_png_bytes_sniffs_signature() {
+        let dir = std::env::temp_dir();
+        let png_path = dir.join(format!("pngme-sniff-{}", std::process::id()));
+        let text_path = dir.join(format!("pngme-sniff-{}.png", std::process::id()));
+        fs::write(&png_path, Png::STANDARD_HEADER).unwrap();
+        fs::write(&text_path, b"definitely not a png").unwrap();
+
+        let png = read_png_bytes(&png_path);
+        let text = read_png_bytes(&text_path);
+        fs::remove_file(&png_path).unwrap();
+        fs::remove_file(&text_path).unwrap();
+
+        assert_eq!(png.unwrap(), Png::STANDARD_HEADER);
+        let err = text.unwrap_err();
+        assert!(matches!(err.downcast_ref::<PngError>(), Some(PngError::InvaldPngHeader)));
+    }
+
+    #[test]
+    fn test_read_raw_chunk() {
+        let dir = std::env::temp_dir();
+        let chunk_path = dir.join(format!("pngme-raw-{}", std::process::id()));
+        let png_path = dir.join(format!("pngme-raw-{}.png", std::process::id()));
+        // the lowercase third letter is only rejected by a strict parse
+        let chunk = Chunk::new(ChunkType::try_from(*b"rust").unwrap(), b"hello".to_vec());
+        fs::write(&chunk_path, chunk.as_bytes()).unwrap();
+        fs::write(&png_path, Png::STANDARD_HEADER).unwrap();
+
+        let lenient = read_raw_chunk(Some(&chunk_path), &ParseOptions::lenient());
+        let strict = read_raw_chunk(Some(&chunk_path), &ParseOptions::strict());
+        let png = read_raw_chunk(Some(&png_path), &ParseOptions::lenient());
+        fs::remove_file(&chunk_path).unwrap();
+        fs::remove_file(&png_path).unwrap();
+
+        assert_eq!(lenient.unwrap().as_bytes(), chunk.as_bytes());
+        assert!(matches!(
+            strict.unwrap_err().downcast_ref::<ChunkError>(),
+            Some(ChunkError::InvalidChunkType(ChunkTypeError::ReservedBitInvalid))
+        ));
+        let err = png.unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkInfoError>(), Some(ChunkInfoError::PngInput)));
+    }
+
+    #[test]
+    fn test_strip_validates() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("pngme-strip-{}.png", std::process::id()));
+        let strip_file = |png: &Png| {
+            fs::write(&path, png.as_bytes()).unwrap();
+            let args = StripArgs { file_path: path.clone(), parse: ParseArgs { strict: false } };
+            let result = strip(args, &global_args());
+            (result, Png::from_path(&path).unwrap())
+        };
+
+        // a private critical chunk is critical, so it is kept
+        let (result, stripped) = strip_file(&png_of_types(&["IHDR", "PrVt", "tEXt", "IDAT", "IEND"]));
+        assert!(result.is_ok());
+        assert_eq!(stripped.chunk_types_str(), vec!["IHDR", "PrVt", "IDAT", "IEND"]);
+
+        // without IEND the result would not validate, so nothing is written
+        let invalid = png_of_types(&["IHDR", "tEXt", "IDAT"]);
+        let (result, unchanged) = strip_file(&invalid);
+        fs::remove_file(&path).unwrap();
+        let err = result.unwrap_err();
+        assert!(matches!(err.downcast_ref::<PngError>(), Some(PngError::IendNotLast)));
+        assert_eq!(unchanged, invalid);
+    }
+
+
+    #[test]
+    fn test_gen_type_empty_name() {
+        let err = gen_type(GenTypeArgs { name: String::new() }, &global_args()).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkTypeError>(), Some(ChunkTypeError::ByteLengthError(0, _))));
+        assert!(gen_type(GenTypeArgs { name: "rust".to_string() }, &global_args()).is_ok());
+    }
+
+    #[test]
+    fn test_explain_casing() {
+        let lines = explain_casing("RuSt", ChunkType::new_private("RuSt").unwrap());
+        assert_eq!(lines, vec![
+            "  R -> r: lowercase (was uppercase), ancillary",
+            "  u -> u: lowercase, private",
+            "  S -> S: uppercase, reserved bit",
+            "  t -> t: lowercase, safe to copy",
+        ]);
+
+        let lines = explain_casing("no", ChunkType::new_private("no").unwrap());
+        assert_eq!(lines[0], "  padded \"no\" with x to 4 letters");
+        assert_eq!(lines[3], "  X: uppercase, reserved bit (padding)");
+        assert_eq!(lines[4], "  x: lowercase, safe to copy (padding)");
+
+        let lines = explain_casing("rustacean", ChunkType::new_private("rustacean").unwrap());
+        assert_eq!(lines[0], "  used the first 4 letters of \"rustacean\"");
+        assert_eq!(lines.len(), 5);
+    }
+
+
+    #[test]
+    fn test_encode_files() {
+        let dir = std::env::temp_dir();
+        let path = |name: &str| dir.join(format!("pngme-batch-{}-{}", std::process::id(), name));
+        let files = vec![path("a.png"), path("not-a-png.png"), path("b.png"), path("missing.png")];
+        let png = png_of_types(&["IHDR", "IDAT", "IEND"]);
+        fs::write(&files[0], png.as_bytes()).unwrap();
+        fs::write(&files[1], b"definitely not a png").unwrap();
+        fs::write(&files[2], png.as_bytes()).unwrap();
+
+        let args = BatchEncodeArgs {
+            chunk_type: "ruSt".to_string(),
+            message: "hello".to_string(),
+            files: files.clone(),
+            encoding: Default::default(),
+            #[cfg(feature = "parallel")]
+            threads: Some(2),
+            parse: ParseArgs { strict: false },
+        };
+        let results = encode_files(&args, b"hello", &EncodeOptions::default()).unwrap();
+        let encoded: Vec<Option<Png>> = files.iter().map(|f| Png::from_path(f).ok()).collect();
+        for file in &files[..3] {
+            fs::remove_file(file).unwrap();
+        }
+
+        // one result per file, in argument order
+        assert_eq!(results.len(), files.len());
+        assert!(results[0].is_ok());
+        assert_eq!(results[1], Err(PngError::InvaldPngHeader.to_string()));
+        assert!(results[2].is_ok());
+        assert!(results[3].is_err());
+
+        for index in [0, 2] {
+            let png = encoded[index].as_ref().unwrap();
+            assert_eq!(png.chunk_types_str(), vec!["IHDR", "IDAT", "ruSt", "IEND"]);
+            assert_eq!(png.chunk_data_by_type("ruSt"), Some(&b"hello"[..]));
+        }
+    }
+
+}
@@ -0,0 +1,218 @@
+//! Helpers for inspecting the boxed errors returned by this crate without
+//! spelling out `downcast_ref` and a `match` at every call site.
+//!
+//! Each helper returns `None` (or `false`) for any other error, including
+//! errors from outside this crate.
+
+use std::error::Error;
+
+use crate::chunk::ChunkError;
+use crate::chunk_type::{ChunkType, ChunkTypeError};
+use crate::in_memory::EncodeError;
+use crate::png::PngError;
+use crate::text_encoding::TextEncodingError;
+
+/// The expected and actual CRC of a `ChunkError::InvalidCrc`.
+pub fn is_invalid_crc(err: &(dyn Error + 'static)) -> Option<(u32, u32)> {
+    match err.downcast_ref::<ChunkError>()? {
+        ChunkError::InvalidCrc(expected, actual) => Some((*expected, *actual)),
+        _ => None,
+    }
+}
+
+/// The declared length and the limit of a `ChunkError::ChunkTooLarge`.
+pub fn is_chunk_too_large(err: &(dyn Error + 'static)) -> Option<(usize, usize)> {
+    match err.downcast_ref::<ChunkError>()? {
+        ChunkError::ChunkTooLarge(length, max) => Some((*length, *max)),
+        _ => None,
+    }
+}
+
+/// The reason of a `ChunkError::InvalidKeyword`.
+pub fn is_invalid_keyword<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a str> {
+    match err.downcast_ref::<ChunkError>()? {
+        ChunkError::InvalidKeyword(reason) => Some(reason),
+        _ => None,
+    }
+}
+
+/// The reason of a `ChunkError::InvalidTextChunk`.
+pub fn is_invalid_text_chunk<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a str> {
+    match err.downcast_ref::<ChunkError>()? {
+        ChunkError::InvalidTextChunk(reason) => Some(reason),
+        _ => None,
+    }
+}
+
+/// Whether the input ended early, either inside a chunk or before the PNG signature.
+pub fn is_input_too_small(err: &(dyn Error + 'static)) -> bool {
+    matches!(
+        err.downcast_ref::<ChunkError>(),
+        Some(ChunkError::InputTooSmall) | Some(ChunkError::UnexpectedEof { .. })
+    ) || matches!(err.downcast_ref::<PngError>(), Some(PngError::InputTooSmall))
+}
+
+/// Whether a stream ended cleanly before the next chunk.
+pub fn is_eof(err: &(dyn Error + 'static)) -> bool {
+    matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::Eof))
+}
+
+/// The expected and received byte counts of a `ChunkError::UnexpectedEof`.
+pub fn is_unexpected_eof(err: &(dyn Error + 'static)) -> Option<(usize, usize)> {
+    match err.downcast_ref::<ChunkError>()? {
+        ChunkError::UnexpectedEof { expected, got } => Some((*expected, *got)),
+        _ => None,
+    }
+}
+
+/// Whether a chunk was rejected for its type while parsing.
+pub fn is_invalid_chunk_type(err: &(dyn Error + 'static)) -> bool {
+    matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InvalidChunkType(_)))
+}
+
+/// Why a chunk was rejected for its type while parsing.
+pub fn invalid_chunk_type_reason<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a ChunkTypeError> {
+    match err.downcast_ref::<ChunkError>()? {
+        ChunkError::InvalidChunkType(reason) => Some(reason),
+        _ => None,
+    }
+}
+
+/// The length of the string given where a 4 letter chunk type was expected.
+pub fn is_byte_length_error(err: &(dyn Error + 'static)) -> Option<usize> {
+    match err.downcast_ref::<ChunkTypeError>()? {
+        ChunkTypeError::ByteLengthError(actual, _) => Some(*actual),
+        _ => None,
+    }
+}
+
+/// The index of the first character of a chunk type that is not an ASCII letter.
+pub fn is_invalid_character(err: &(dyn Error + 'static)) -> Option<usize> {
+    match err.downcast_ref::<ChunkTypeError>()? {
+        ChunkTypeError::InvalidCharacter(index) => Some(*index),
+        _ => None,
+    }
+}
+
+/// Whether a chunk type was rejected for its lowercase reserved bit.
+pub fn is_reserved_bit_invalid(err: &(dyn Error + 'static)) -> bool {
+    matches!(err.downcast_ref::<ChunkTypeError>(), Some(ChunkTypeError::ReservedBitInvalid))
+}
+
+/// Whether a `Png` had no chunk of the requested type.
+pub fn is_chunk_type_not_found(err: &(dyn Error + 'static)) -> bool {
+    matches!(err.downcast_ref::<PngError>(), Some(PngError::ChunkTypeNotFound))
+}
+
+/// Whether the input did not start with the PNG signature.
+pub fn is_invalid_png_header(err: &(dyn Error + 'static)) -> bool {
+    matches!(err.downcast_ref::<PngError>(), Some(PngError::InvaldPngHeader))
+}
+
+/// The index and number of chunks of a `PngError::IndexOutOfBounds`.
+pub fn is_index_out_of_bounds(err: &(dyn Error + 'static)) -> Option<(usize, usize)> {
+    match err.downcast_ref::<PngError>()? {
+        PngError::IndexOutOfBounds(index, len) => Some((*index, *len)),
+        _ => None,
+    }
+}
+
+/// Whether `IHDR` was not the first chunk or `IEND` was not the last.
+pub fn is_misplaced_chunk(err: &(dyn Error + 'static)) -> bool {
+    matches!(
+        err.downcast_ref::<PngError>(),
+        Some(PngError::IhdrNotFirst) | Some(PngError::IendNotLast)
+    )
+}
+
+/// The chunk type of a `PngError::DuplicateChunk`.
+pub fn is_duplicate_chunk(err: &(dyn Error + 'static)) -> Option<ChunkType> {
+    match err.downcast_ref::<PngError>()? {
+        PngError::DuplicateChunk(chunk_type) => Some(*chunk_type),
+        _ => None,
+    }
+}
+
+/// The chunk type and the broken rule of a `PngError::MisorderedChunk`.
+pub fn is_misordered_chunk(err: &(dyn Error + 'static)) -> Option<(ChunkType, &'static str)> {
+    match err.downcast_ref::<PngError>()? {
+        PngError::MisorderedChunk(chunk_type, rule) => Some((*chunk_type, *rule)),
+        _ => None,
+    }
+}
+
+/// The message size and the limit of an `EncodeError::MessageTooLarge`.
+pub fn is_message_too_large(err: &(dyn Error + 'static)) -> Option<(usize, usize)> {
+    match err.downcast_ref::<EncodeError>()? {
+        EncodeError::MessageTooLarge(size, limit) => Some((*size, *limit)),
+        _ => None,
+    }
+}
+
+/// The projected size and the limit of an `EncodeError::OutputTooLarge`.
+pub fn is_output_too_large(err: &(dyn Error + 'static)) -> Option<(usize, usize)> {
+    match err.downcast_ref::<EncodeError>()? {
+        EncodeError::OutputTooLarge(size, limit) => Some((*size, *limit)),
+        _ => None,
+    }
+}
+
+/// The character of a `TextEncodingError::Unmappable`.
+pub fn is_unmappable(err: &(dyn Error + 'static)) -> Option<char> {
+    match err.downcast_ref::<TextEncodingError>()? {
+        TextEncodingError::Unmappable(c) => Some(*c),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error as BoxedError;
+
+    #[test]
+    fn test_is_invalid_crc() {
+        let err: BoxedError = Box::new(ChunkError::InvalidCrc(1, 2));
+        assert_eq!(is_invalid_crc(err.as_ref()), Some((1, 2)));
+        assert_eq!(is_chunk_too_large(err.as_ref()), None);
+        assert!(!is_input_too_small(err.as_ref()));
+    }
+
+    #[test]
+    fn test_is_input_too_small() {
+        let chunk: BoxedError = Box::new(ChunkError::InputTooSmall);
+        let png: BoxedError = Box::new(PngError::InputTooSmall);
+        assert!(is_input_too_small(chunk.as_ref()));
+        assert!(is_input_too_small(png.as_ref()));
+    }
+
+    #[test]
+    fn test_invalid_chunk_type() {
+        let err: BoxedError = Box::new(ChunkError::InvalidChunkType(ChunkTypeError::InvalidCharacter(2)));
+        assert!(is_invalid_chunk_type(err.as_ref()));
+        assert!(matches!(invalid_chunk_type_reason(err.as_ref()), Some(ChunkTypeError::InvalidCharacter(2))));
+
+        let err: BoxedError = Box::new(ChunkError::InputTooSmall);
+        assert!(!is_invalid_chunk_type(err.as_ref()));
+        assert!(invalid_chunk_type_reason(err.as_ref()).is_none());
+    }
+
+    #[test]
+    fn test_png_error_helpers() {
+        let err: BoxedError = Box::new(PngError::IndexOutOfBounds(5, 3));
+        assert_eq!(is_index_out_of_bounds(err.as_ref()), Some((5, 3)));
+        assert!(!is_chunk_type_not_found(err.as_ref()));
+
+        let iend: ChunkType = std::str::FromStr::from_str("IEND").unwrap();
+        let err: BoxedError = Box::new(PngError::DuplicateChunk(iend));
+        assert_eq!(is_duplicate_chunk(err.as_ref()), Some(iend));
+    }
+
+    #[test]
+    fn test_foreign_error() {
+        let err: BoxedError = Box::new(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(is_invalid_crc(err.as_ref()), None);
+        assert_eq!(is_message_too_large(err.as_ref()), None);
+        assert!(!is_invalid_png_header(err.as_ref()));
+    }
+}
@@ -0,0 +1,68 @@
+//! Chunks sent back to back without the PNG signature, for transports that
+//! don't need a valid PNG file, e.g. a custom socket protocol.
+
+use std::convert::TryFrom;
+
+use crate::Result;
+use crate::chunk::Chunk;
+
+/// Serializes `chunks` in order with nothing between or around them: the
+/// bytes of a `Png` holding the same chunks, minus the 8 byte header.
+pub fn encode_chunks_framed(chunks: &[Chunk]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(chunks.iter().map(Chunk::serialized_len).sum());
+    for chunk in chunks {
+        bytes.extend(chunk.iter_bytes());
+    }
+    bytes
+}
+
+/// Parses bytes written by `encode_chunks_framed`. Every chunk is parsed
+/// strictly and the input must end exactly after the last one, so trailing
+/// bytes are an error rather than being ignored.
+pub fn decode_chunks_framed(bytes: &[u8]) -> Result<Vec<Chunk>> {
+    let mut chunks = vec![];
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let chunk = Chunk::try_from(rest)?;
+        rest = &rest[chunk.serialized_len()..];
+        chunks.push(chunk);
+    }
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::ChunkError;
+    use crate::chunk_type::ChunkType;
+    use crate::png::Png;
+    use std::str::FromStr;
+
+    fn testing_chunks() -> Vec<Chunk> {
+        vec![
+            Chunk::new(ChunkType::from_str("FrSt").unwrap(), b"I am the first chunk".to_vec()),
+            Chunk::new(ChunkType::from_str("miDl").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("LASt").unwrap(), b"I am the last chunk".to_vec()),
+        ]
+    }
+
+    #[test]
+    fn test_framed_round_trip() {
+        let bytes = encode_chunks_framed(&testing_chunks());
+        let png_bytes = Png::from_chunks(testing_chunks()).as_bytes();
+        assert_eq!(bytes, png_bytes[Png::STANDARD_HEADER.len()..]);
+        assert_eq!(decode_chunks_framed(&bytes).unwrap(), testing_chunks());
+        assert!(decode_chunks_framed(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_framed_trailing_bytes() {
+        let mut bytes = encode_chunks_framed(&testing_chunks());
+        bytes.extend_from_slice(b"extra");
+        let err = decode_chunks_framed(&bytes).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InputTooSmall)));
+
+        let bytes = encode_chunks_framed(&testing_chunks());
+        assert!(decode_chunks_framed(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
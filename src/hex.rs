@@ -0,0 +1,110 @@
+use std::fmt::Display;
+
+use pngme::Result;
+
+/// Lowercase hex representation of `bytes`, two digits per byte.
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A classic hex editor view of `bytes`: one line per 16 bytes with the
+/// offset, the bytes in hex and an ASCII gutter where unprintable bytes are `.`
+pub fn dump(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, row) in bytes.chunks(16).enumerate() {
+        let mut hex = String::new();
+        for (i, b) in row.iter().enumerate() {
+            if i == 8 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x} ", b));
+        }
+        let ascii: String = row
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}  {:<49} |{}|\n", line * 16, hex, ascii));
+    }
+    out
+}
+
+/// Decodes a hex string, accepting upper and lower case digits.
+pub fn decode(hex: &str) -> Result<Vec<u8>> {
+    let digits = hex.as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return Err(Box::from(HexError::OddLength(digits.len())));
+    }
+
+    digits
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| Ok(digit(pair[0], i * 2)? << 4 | digit(pair[1], i * 2 + 1)?))
+        .collect()
+}
+
+fn digit(c: u8, index: usize) -> Result<u8> {
+    (c as char)
+        .to_digit(16)
+        .map(|d| d as u8)
+        .ok_or_else(|| Box::from(HexError::InvalidDigit(c as char, index)))
+}
+
+// Hex errors
+#[derive(Debug)]
+pub enum HexError {
+    // Hex strings need two digits per byte
+    OddLength(usize),
+
+    // Character at the given index is not a hex digit
+    InvalidDigit(char, usize),
+}
+
+impl std::error::Error for HexError {}
+
+impl Display for HexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HexError::OddLength(len) => {
+                write!(f, "Hex string has odd length {}, expected two digits per byte", len)
+            },
+            HexError::InvalidDigit(c, index) => {
+                write!(f, "Invalid hex digit {:?} at index {}", c, index)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump() {
+        let dump = dump(b"Hello, PNG!\x00\x01\x89\xffabcdefgh");
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "00000000  48 65 6c 6c 6f 2c 20 50  4e 47 21 00 01 89 ff 61  |Hello, PNG!....a|"
+        );
+        assert_eq!(
+            lines[1],
+            "00000010  62 63 64 65 66 67 68                              |bcdefgh|"
+        );
+        assert_eq!(super::dump(b""), "");
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = vec![0, 1, 127, 128, 255];
+        assert_eq!(encode(&bytes), "00017f80ff");
+        assert_eq!(decode("00017F80ff").unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_hex_invalid() {
+        assert!(decode("abc").is_err());
+        assert!(decode("zz").is_err());
+        assert_eq!(decode("").unwrap(), Vec::<u8>::new());
+    }
+}
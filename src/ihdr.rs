@@ -0,0 +1,165 @@
+//! The image header stored in the `IHDR` chunk
+//! http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.IHDR
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Display;
+
+use crate::Result;
+
+/// How pixels are stored, from the color type byte of `IHDR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorType {
+    Grayscale,
+    Rgb,
+    Indexed,
+    GrayscaleAlpha,
+    Rgba,
+}
+
+impl ColorType {
+    /// Whether `bit_depth` is allowed for this color type.
+    pub fn allows_bit_depth(&self, bit_depth: u8) -> bool {
+        match self {
+            ColorType::Grayscale => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+            ColorType::Indexed => matches!(bit_depth, 1 | 2 | 4 | 8),
+            ColorType::Rgb | ColorType::GrayscaleAlpha | ColorType::Rgba => matches!(bit_depth, 8 | 16),
+        }
+    }
+
+    /// Whether pixels carry their own alpha channel.
+    pub fn has_alpha(&self) -> bool {
+        matches!(self, ColorType::GrayscaleAlpha | ColorType::Rgba)
+    }
+}
+
+impl TryFrom<u8> for ColorType {
+    type Error = crate::Error;
+
+    fn try_from(color_type: u8) -> Result<Self> {
+        match color_type {
+            0 => Ok(ColorType::Grayscale),
+            2 => Ok(ColorType::Rgb),
+            3 => Ok(ColorType::Indexed),
+            4 => Ok(ColorType::GrayscaleAlpha),
+            6 => Ok(ColorType::Rgba),
+            _ => Err(Box::from(IhdrError::InvalidColorType(color_type))),
+        }
+    }
+}
+
+impl Display for ColorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColorType::Grayscale => "grayscale",
+            ColorType::Rgb => "RGB",
+            ColorType::Indexed => "indexed",
+            ColorType::GrayscaleAlpha => "grayscale with alpha",
+            ColorType::Rgba => "RGBA",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The fields of an `IHDR` chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ihdr {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub color_type: ColorType,
+    pub compression_method: u8,
+    pub filter_method: u8,
+    pub interlace_method: u8,
+}
+
+impl Ihdr {
+    /// Parses the 13 data bytes of an `IHDR` chunk, checking that the bit
+    /// depth is allowed for the color type.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() != 13 {
+            return Err(Box::from(IhdrError::InvalidLength(data.len())));
+        }
+        let color_type = ColorType::try_from(data[9])?;
+        let bit_depth = data[8];
+        if !color_type.allows_bit_depth(bit_depth) {
+            return Err(Box::from(IhdrError::InvalidBitDepth(bit_depth, color_type)));
+        }
+        Ok(Ihdr {
+            width: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            height: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            bit_depth,
+            color_type,
+            compression_method: data[10],
+            filter_method: data[11],
+            interlace_method: data[12],
+        })
+    }
+}
+
+impl Display for Ihdr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}, {}-bit {}", self.width, self.height, self.bit_depth, self.color_type)
+    }
+}
+
+// IHDR Errors
+#[derive(Debug)]
+pub enum IhdrError {
+    // IHDR data must be exactly 13 bytes (actual length)
+    InvalidLength(usize),
+
+    // Color type is not one of 0, 2, 3, 4 or 6
+    InvalidColorType(u8),
+
+    // Bit depth is not allowed for the color type
+    InvalidBitDepth(u8, ColorType),
+}
+
+impl std::error::Error for IhdrError {}
+
+impl Display for IhdrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IhdrError::InvalidLength(length) => {
+                write!(f, "IHDR data must be 13 bytes, got {}", length)
+            },
+            IhdrError::InvalidColorType(color_type) => {
+                write!(f, "Invalid IHDR color type {}", color_type)
+            },
+            IhdrError::InvalidBitDepth(bit_depth, color_type) => {
+                write!(f, "Bit depth {} is not allowed for {} images", bit_depth, color_type)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ihdr_data(bit_depth: u8, color_type: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&100u32.to_be_bytes());
+        data.extend_from_slice(&50u32.to_be_bytes());
+        data.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_ihdr_parse() {
+        let ihdr = Ihdr::parse(&ihdr_data(8, 6)).unwrap();
+        assert_eq!((ihdr.width, ihdr.height), (100, 50));
+        assert_eq!(ihdr.color_type, ColorType::Rgba);
+        assert_eq!(ihdr.to_string(), "100x50, 8-bit RGBA");
+    }
+
+    #[test]
+    fn test_ihdr_invalid() {
+        assert!(Ihdr::parse(&ihdr_data(8, 6)[..12]).is_err());
+        assert!(Ihdr::parse(&ihdr_data(8, 5)).is_err());
+        assert!(Ihdr::parse(&ihdr_data(16, 3)).is_err());
+        assert!(Ihdr::parse(&ihdr_data(4, 2)).is_err());
+        assert!(Ihdr::parse(&ihdr_data(1, 0)).is_ok());
+    }
+}
@@ -0,0 +1,328 @@
+//! Encoding and decoding on byte buffers, without touching the filesystem.
+//! The CLI commands are thin wrappers around these.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::chunk::{Chunk, ParseOptions};
+use crate::chunk_type::ChunkType;
+use crate::metadata::{MetadataError, PayloadEncoding, PayloadInfo};
+use crate::png::{Png, PngBuilder};
+use crate::Result;
+
+/// The largest message `encode_in_memory` accepts by default: 10 MiB.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024;
+
+/// Where `encode_in_memory_with` places the new chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Placement {
+    /// Just in front of `IEND`, or at the end if there is no `IEND`
+    #[default]
+    BeforeIend,
+    /// Directly after the last chunk of the given type
+    AfterType(String),
+    /// At the given chunk index
+    At(usize),
+}
+
+/// Options for `encode_in_memory_with`.
+#[derive(Debug, Clone)]
+pub struct EncodeOptions {
+    /// How the input PNG is parsed
+    pub parse: ParseOptions,
+    /// Run `Png::validate` on the result and fail if it is not a valid PNG
+    pub validate: bool,
+    /// Reject messages longer than this many bytes
+    pub max_message_size: usize,
+    /// Reject results whose serialized size would exceed this many bytes
+    pub max_output_size: Option<usize>,
+    /// Where the new chunk goes
+    pub placement: Placement,
+    /// Also add a `pnGm` chunk describing how the message is stored
+    pub version_chunk: bool,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            parse: ParseOptions::default(),
+            validate: false,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_output_size: None,
+            placement: Placement::default(),
+            version_chunk: false,
+        }
+    }
+}
+
+/// Stores `message` in a new chunk of `chunk_type` in front of `IEND` and
+/// returns the bytes of the modified PNG.
+pub fn encode_in_memory(png_bytes: &[u8], chunk_type: &str, message: &[u8]) -> Result<Vec<u8>> {
+    encode_in_memory_with(png_bytes, chunk_type, message, &EncodeOptions::default())
+}
+
+/// `encode_in_memory` with control over parsing and validation.
+pub fn encode_in_memory_with(
+    png_bytes: &[u8],
+    chunk_type: &str,
+    message: &[u8],
+    options: &EncodeOptions,
+) -> Result<Vec<u8>> {
+    Ok(encode_png_with(png_bytes, chunk_type, message, options)?.as_bytes())
+}
+
+/// `encode_in_memory_with`, but returns the modified `Png` instead of its
+/// bytes, for callers that still want to inspect or write it themselves.
+pub fn encode_png_with(
+    png_bytes: &[u8],
+    chunk_type: &str,
+    message: &[u8],
+    options: &EncodeOptions,
+) -> Result<Png> {
+    if message.len() > options.max_message_size {
+        return Err(Box::from(EncodeError::MessageTooLarge(message.len(), options.max_message_size)));
+    }
+
+    let mut png = Png::parse(png_bytes, &options.parse)?;
+    let chunk_type = ChunkType::from_str(chunk_type)?;
+    let chunk = Chunk::new(chunk_type, message.to_vec());
+    match &options.placement {
+        Placement::BeforeIend => {
+            let mut builder = PngBuilder::from_png(png);
+            builder.push_chunk(chunk)?;
+            png = builder.finish();
+        }
+        Placement::AfterType(after) => png.insert_after_type(after, chunk)?,
+        Placement::At(index) => png.insert_chunk(*index, chunk)?,
+    }
+    if options.version_chunk {
+        // one pnGm per message chunk type, so re-encoding replaces the old one
+        let info = PayloadInfo::new(chunk_type, PayloadEncoding::Plain).to_chunk()?;
+        match png.chunks().iter().position(|c| describes(c, chunk_type)) {
+            Some(index) => *png.chunk_at_mut(index).expect("index from position") = info,
+            None => png.append_chunk(info),
+        }
+    }
+
+    if options.validate {
+        png.validate()?;
+    }
+    if let Some(limit) = options.max_output_size {
+        let size = png.total_size();
+        if size > limit {
+            return Err(Box::from(EncodeError::OutputTooLarge(size, limit)));
+        }
+    }
+    Ok(png)
+}
+
+/// Returns the data of the first chunk of `chunk_type`, or `None` if there is none.
+pub fn decode_in_memory(png_bytes: &[u8], chunk_type: &str) -> Result<Option<Vec<u8>>> {
+    decode_in_memory_with(png_bytes, chunk_type, &ParseOptions::default())
+}
+
+/// `decode_in_memory` with control over parsing. If a `pnGm` chunk describes
+/// `chunk_type`, the message must be stored in a way this build can decode.
+pub fn decode_in_memory_with(
+    png_bytes: &[u8],
+    chunk_type: &str,
+    options: &ParseOptions,
+) -> Result<Option<Vec<u8>>> {
+    let png = Png::parse(png_bytes, options)?;
+    if let Some(info) = payload_info(&png, ChunkType::from_str(chunk_type)?)? {
+        if info.encoding != PayloadEncoding::Plain {
+            return Err(Box::from(MetadataError::UnsupportedEncoding(info.encoding)));
+        }
+    }
+    Ok(png.chunk_data_by_type(chunk_type).map(<[u8]>::to_vec))
+}
+
+/// The first `pnGm` chunk describing messages of `chunk_type`, if there is one.
+/// `pnGm` chunks for other chunk types are never parsed. A matching one that
+/// this build can't read, e.g. a newer format version or an unknown encoding,
+/// is an error, so the message is never misread as plain data.
+pub fn payload_info(png: &Png, chunk_type: ChunkType) -> Result<Option<PayloadInfo>> {
+    match png.chunks().iter().find(|c| describes(c, chunk_type)) {
+        Some(chunk) => Ok(Some(PayloadInfo::from_chunk(chunk)?)),
+        None => Ok(None),
+    }
+}
+
+// Whether `chunk` is a pnGm chunk whose chunk type field is `chunk_type`
+fn describes(chunk: &Chunk, chunk_type: ChunkType) -> bool {
+    chunk.chunk_type().bytes() == PayloadInfo::CHUNK_TYPE
+        && chunk.data().get(2..6) == Some(chunk_type.as_ref())
+}
+
+// Encode errors
+#[derive(Debug)]
+pub enum EncodeError {
+    // Message is larger than the configured maximum (size, limit)
+    MessageTooLarge(usize, usize),
+
+    // Resulting PNG is larger than the configured maximum (projected size, limit)
+    OutputTooLarge(usize, usize),
+}
+
+impl std::error::Error for EncodeError {}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::MessageTooLarge(size, limit) => write!(
+                f,
+                "Message is {} bytes, larger than the maximum of {} bytes",
+                size,
+                limit
+            ),
+            EncodeError::OutputTooLarge(size, limit) => write!(
+                f,
+                "Output would be {} bytes, larger than the maximum of {} bytes",
+                size,
+                limit
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn testing_png_bytes() -> Vec<u8> {
+        Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![1, 2, 3]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ])
+        .as_bytes()
+    }
+
+    #[test]
+    fn test_encode_decode_in_memory() {
+        let bytes = encode_in_memory(&testing_png_bytes(), "ruSt", &[0, 159, 146, 150]).unwrap();
+        let message = decode_in_memory(&bytes, "ruSt").unwrap();
+        assert_eq!(message, Some(vec![0, 159, 146, 150]));
+    }
+
+    #[test]
+    fn test_encode_in_memory_version_chunk() {
+        let options = EncodeOptions { version_chunk: true, ..EncodeOptions::default() };
+        let bytes = encode_in_memory_with(&testing_png_bytes(), "ruSt", b"message", &options).unwrap();
+        let png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        assert_eq!(png.chunk_types_str(), ["IHDR", "IDAT", "ruSt", "pnGm", "IEND"]);
+
+        let info = payload_info(&png, ChunkType::from_str("ruSt").unwrap()).unwrap().unwrap();
+        assert_eq!(info.encoding, PayloadEncoding::Plain);
+        assert_eq!(decode_in_memory(&bytes, "ruSt").unwrap(), Some(b"message".to_vec()));
+        assert_eq!(payload_info(&png, ChunkType::from_str("miDl").unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_encode_in_memory_version_chunk_replaced() {
+        let options = EncodeOptions { version_chunk: true, ..EncodeOptions::default() };
+        let bytes = encode_in_memory_with(&testing_png_bytes(), "ruSt", b"first", &options).unwrap();
+        let bytes = encode_in_memory_with(&bytes, "miDl", b"other", &options).unwrap();
+        let bytes = encode_in_memory_with(&bytes, "ruSt", b"second", &options).unwrap();
+        let png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        assert_eq!(png.count_of_type("pnGm"), 2);
+        assert!(payload_info(&png, ChunkType::from_str("ruSt").unwrap()).unwrap().is_some());
+        assert!(payload_info(&png, ChunkType::from_str("miDl").unwrap()).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_decode_in_memory_foreign_metadata() {
+        let bytes = encode_in_memory(&testing_png_bytes(), "ruSt", b"message").unwrap();
+        let mut png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        let pngm = ChunkType::try_from(PayloadInfo::CHUNK_TYPE).unwrap();
+        // too short to name a chunk type, and a newer version describing miDl
+        png.append_chunk(Chunk::new(pngm, b"\x01".to_vec()));
+        png.append_chunk(Chunk::new(pngm, b"\x07\x09miDl???".to_vec()));
+
+        assert_eq!(decode_in_memory(&png.as_bytes(), "ruSt").unwrap(), Some(b"message".to_vec()));
+        assert_eq!(payload_info(&png, ChunkType::from_str("ruSt").unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_decode_in_memory_unreadable_metadata() {
+        let bytes = encode_in_memory(&testing_png_bytes(), "ruSt", b"message").unwrap();
+        let pngm = ChunkType::try_from(PayloadInfo::CHUNK_TYPE).unwrap();
+        let mut png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        png.append_chunk(Chunk::new(pngm, b"\x02\x00ruSt".to_vec()));
+        let err = decode_in_memory(&png.as_bytes(), "ruSt").unwrap_err();
+        assert!(matches!(err.downcast_ref::<MetadataError>(), Some(MetadataError::UnsupportedVersion(2))));
+
+        let mut png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        png.append_chunk(Chunk::new(pngm, b"\x01\x09ruSt".to_vec()));
+        let err = payload_info(&png, ChunkType::from_str("ruSt").unwrap()).unwrap_err();
+        assert!(matches!(err.downcast_ref::<MetadataError>(), Some(MetadataError::UnknownEncoding(9))));
+    }
+
+    #[test]
+    fn test_decode_in_memory_unsupported_encoding() {
+        let bytes = encode_in_memory(&testing_png_bytes(), "ruSt", b"message").unwrap();
+        let info = PayloadInfo::new(ChunkType::from_str("ruSt").unwrap(), PayloadEncoding::Encrypted);
+        let mut png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        png.append_chunk(info.to_chunk().unwrap());
+
+        let err = decode_in_memory(&png.as_bytes(), "ruSt").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<MetadataError>(),
+            Some(MetadataError::UnsupportedEncoding(PayloadEncoding::Encrypted))
+        ));
+    }
+
+    #[test]
+    fn test_encode_in_memory_keeps_iend_last() {
+        let bytes = encode_in_memory(&testing_png_bytes(), "ruSt", b"message").unwrap();
+        let png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        assert_eq!(png.position_of_type("ruSt"), Some(2));
+    }
+
+    #[test]
+    fn test_decode_in_memory_missing() {
+        assert_eq!(decode_in_memory(&testing_png_bytes(), "ruSt").unwrap(), None);
+        assert!(decode_in_memory(&testing_png_bytes(), "ru5t").is_err());
+    }
+
+    #[test]
+    fn test_encode_in_memory_max_message_size() {
+        let options = EncodeOptions { max_message_size: 4, ..EncodeOptions::default() };
+        assert!(encode_in_memory_with(&testing_png_bytes(), "ruSt", b"four", &options).is_ok());
+        assert!(encode_in_memory_with(&testing_png_bytes(), "ruSt", b"five!", &options).is_err());
+    }
+
+    #[test]
+    fn test_encode_in_memory_max_output_size() {
+        // 8 byte header, 25 byte IHDR, 15 byte IDAT, 12 byte IEND, plus a 16 byte chunk
+        let options = EncodeOptions { max_output_size: Some(76), ..EncodeOptions::default() };
+        assert!(encode_in_memory_with(&testing_png_bytes(), "ruSt", b"four", &options).is_ok());
+        let err = encode_in_memory_with(&testing_png_bytes(), "ruSt", b"five!", &options).unwrap_err();
+        assert_eq!(crate::errors::is_output_too_large(err.as_ref()), Some((77, 76)));
+    }
+
+    #[test]
+    fn test_encode_in_memory_placement() {
+        let options = EncodeOptions {
+            placement: Placement::AfterType("IHDR".to_string()),
+            ..EncodeOptions::default()
+        };
+        let bytes = encode_in_memory_with(&testing_png_bytes(), "ruSt", b"message", &options).unwrap();
+        let png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        assert_eq!(png.position_of_type("ruSt"), Some(1));
+
+        let options = EncodeOptions { placement: Placement::At(2), ..EncodeOptions::default() };
+        let bytes = encode_in_memory_with(&testing_png_bytes(), "ruSt", b"message", &options).unwrap();
+        let png = Png::parse(&bytes, &ParseOptions::strict()).unwrap();
+        assert_eq!(png.position_of_type("ruSt"), Some(2));
+    }
+
+    #[test]
+    fn test_encode_in_memory_validate() {
+        let bytes = Png::from_chunks(vec![]).as_bytes();
+        let options = EncodeOptions { validate: true, ..EncodeOptions::default() };
+        assert!(encode_in_memory(&bytes, "ruSt", b"message").is_ok());
+        assert!(encode_in_memory_with(&bytes, "ruSt", b"message", &options).is_err());
+    }
+}
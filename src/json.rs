@@ -0,0 +1,249 @@
+use std::error::Error;
+use std::fmt::Write;
+
+use pngme::chunk::ChunkError;
+use pngme::chunk_type::ChunkTypeError;
+use pngme::in_memory::EncodeError;
+use pngme::metadata::MetadataError;
+use pngme::png::PngError;
+use pngme::text_encoding::TextEncodingError;
+
+use crate::commands::{ChunkInfoError, RemoveError};
+use crate::hex::HexError;
+
+/// Builds a flat JSON object one field at a time.
+#[derive(Debug, Default)]
+pub struct JsonObject {
+    fields: Vec<(String, String)>,
+}
+
+impl JsonObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn string(mut self, key: &str, value: &str) -> Self {
+        self.fields.push((key.to_string(), escape(value)));
+        self
+    }
+
+    pub fn number<N: Into<u64>>(mut self, key: &str, value: N) -> Self {
+        self.fields.push((key.to_string(), value.into().to_string()));
+        self
+    }
+
+    pub fn boolean(mut self, key: &str, value: bool) -> Self {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds a value that is already valid JSON, e.g. a nested object or array.
+    pub fn raw(mut self, key: &str, json: String) -> Self {
+        self.fields.push((key.to_string(), json));
+        self
+    }
+
+    pub fn finish(self) -> String {
+        let fields: Vec<String> = self.fields
+            .into_iter()
+            .map(|(key, value)| format!("{}:{}", escape(&key), value))
+            .collect();
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// Joins already-encoded JSON values into an array.
+pub fn array<I: IntoIterator<Item = String>>(values: I) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<_>>().join(","))
+}
+
+/// Quotes and escapes a string as a JSON string literal.
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Describes an error as a JSON object with its kind, variant and message
+/// plus any values carried by the variant.
+pub fn error(err: &(dyn Error + 'static)) -> String {
+    let (kind, variant, object) = if let Some(e) = err.downcast_ref::<ChunkError>() {
+        let (variant, object) = match e {
+            ChunkError::InvalidCrc(expected, actual) => ("InvalidCrc", JsonObject::new()
+                .number("expected", *expected)
+                .number("actual", *actual)),
+            ChunkError::InputTooSmall => ("InputTooSmall", JsonObject::new()),
+            ChunkError::InvalidChunkType(reason) => ("InvalidChunkType", JsonObject::new()
+                .string("reason", &reason.to_string())),
+            ChunkError::ChunkTooLarge(length, max) => ("ChunkTooLarge", JsonObject::new()
+                .number("length", *length as u64)
+                .number("max", *max as u64)),
+            ChunkError::InvalidKeyword(_) => ("InvalidKeyword", JsonObject::new()),
+            ChunkError::CriticalChunkType(_) => ("CriticalChunkType", JsonObject::new()),
+            ChunkError::InvalidTextChunk(reason) => ("InvalidTextChunk", JsonObject::new()
+                .string("reason", reason)),
+            ChunkError::NotTextChunk(chunk_type) => ("NotTextChunk", JsonObject::new()
+                .string("chunk_type", &chunk_type.to_string())),
+            ChunkError::InvalidCompressedData(reason) => ("InvalidCompressedData", JsonObject::new()
+                .string("reason", reason)),
+            ChunkError::Eof => ("Eof", JsonObject::new()),
+            ChunkError::UnexpectedEof { expected, got } => ("UnexpectedEof", JsonObject::new()
+                .number("expected", *expected as u64)
+                .number("got", *got as u64)),
+        };
+        ("ChunkError", variant, object)
+    } else if let Some(e) = err.downcast_ref::<ChunkTypeError>() {
+        let (variant, object) = match e {
+            ChunkTypeError::ByteLengthError(actual, input) => ("ByteLengthError", JsonObject::new()
+                .number("actual", *actual as u64)
+                .string("input", input)),
+            ChunkTypeError::InvalidCharacter(index) => ("InvalidCharacter", JsonObject::new()
+                .number("index", *index as u64)),
+            ChunkTypeError::ReservedBitInvalid => ("ReservedBitInvalid", JsonObject::new()),
+        };
+        ("ChunkTypeError", variant, object)
+    } else if let Some(e) = err.downcast_ref::<EncodeError>() {
+        let (variant, object) = match e {
+            EncodeError::MessageTooLarge(size, limit) => ("MessageTooLarge", JsonObject::new()
+                .number("size", *size as u64)
+                .number("limit", *limit as u64)),
+            EncodeError::OutputTooLarge(size, limit) => ("OutputTooLarge", JsonObject::new()
+                .number("size", *size as u64)
+                .number("limit", *limit as u64)),
+        };
+        ("EncodeError", variant, object)
+    } else if let Some(e) = err.downcast_ref::<PngError>() {
+        let (variant, object) = match e {
+            PngError::ChunkTypeNotFound => ("ChunkTypeNotFound", JsonObject::new()),
+            PngError::InputTooSmall => ("InputTooSmall", JsonObject::new()),
+            PngError::InvaldPngHeader => ("InvaldPngHeader", JsonObject::new()),
+            PngError::IndexOutOfBounds(_, _) => ("IndexOutOfBounds", JsonObject::new()),
+            PngError::IhdrNotFirst => ("IhdrNotFirst", JsonObject::new()),
+            PngError::IendNotLast => ("IendNotLast", JsonObject::new()),
+            PngError::DuplicateChunk(chunk_type) => ("DuplicateChunk", JsonObject::new()
+                .string("chunk_type", &chunk_type.to_string())),
+            PngError::MisorderedChunk(chunk_type, rule) => ("MisorderedChunk", JsonObject::new()
+                .string("chunk_type", &chunk_type.to_string())
+                .string("rule", rule)),
+        };
+        ("PngError", variant, object)
+    } else if let Some(e) = err.downcast_ref::<MetadataError>() {
+        let variant = match e {
+            MetadataError::NotMetadata(_) => "NotMetadata",
+            MetadataError::TooShort(_) => "TooShort",
+            MetadataError::UnsupportedVersion(_) => "UnsupportedVersion",
+            MetadataError::UnknownEncoding(_) => "UnknownEncoding",
+            MetadataError::UnsupportedEncoding(_) => "UnsupportedEncoding",
+        };
+        ("MetadataError", variant, JsonObject::new())
+    } else if let Some(e) = err.downcast_ref::<TextEncodingError>() {
+        let variant = match e {
+            TextEncodingError::UnknownEncoding(_) => "UnknownEncoding",
+            TextEncodingError::FeatureDisabled(_) => "FeatureDisabled",
+            TextEncodingError::Unmappable(_) => "Unmappable",
+        };
+        ("TextEncodingError", variant, JsonObject::new())
+    } else if let Some(e) = err.downcast_ref::<RemoveError>() {
+        let (variant, object) = match e {
+            RemoveError::CriticalChunk(chunk_type) => ("CriticalChunk", JsonObject::new()
+                .string("chunk_type", chunk_type)),
+        };
+        ("RemoveError", variant, object)
+    } else if let Some(e) = err.downcast_ref::<ChunkInfoError>() {
+        let variant = match e {
+            ChunkInfoError::PngInput => "PngInput",
+        };
+        ("ChunkInfoError", variant, JsonObject::new())
+    } else if let Some(e) = err.downcast_ref::<HexError>() {
+        let variant = match e {
+            HexError::OddLength(_) => "OddLength",
+            HexError::InvalidDigit(_, _) => "InvalidDigit",
+        };
+        ("HexError", variant, JsonObject::new())
+    } else if let Some(e) = err.downcast_ref::<std::io::Error>() {
+        // the kind, e.g. NotFound, rather than how the error is stored
+        ("IoError", io_kind_name(e.kind()), JsonObject::new())
+    } else if err.is::<std::str::Utf8Error>() {
+        ("Utf8Error", "Utf8Error", JsonObject::new())
+    } else {
+        ("Error", "Error", JsonObject::new())
+    };
+
+    JsonObject::new()
+        .string("error", variant)
+        .string("kind", kind)
+        .string("message", &err.to_string())
+        .raw("details", object.finish())
+        .finish()
+}
+
+// The name of the io errors users are likely to hit
+fn io_kind_name(kind: std::io::ErrorKind) -> &'static str {
+    use std::io::ErrorKind;
+    match kind {
+        ErrorKind::NotFound => "NotFound",
+        ErrorKind::PermissionDenied => "PermissionDenied",
+        ErrorKind::AlreadyExists => "AlreadyExists",
+        ErrorKind::InvalidInput => "InvalidInput",
+        ErrorKind::InvalidData => "InvalidData",
+        ErrorKind::UnexpectedEof => "UnexpectedEof",
+        ErrorKind::BrokenPipe => "BrokenPipe",
+        ErrorKind::Interrupted => "Interrupted",
+        _ => "Other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+
+    #[test]
+    fn test_object() {
+        let json = JsonObject::new()
+            .string("type", "ruSt")
+            .number("length", 5u32)
+            .boolean("critical", false)
+            .raw("list", array(vec!["1".to_string(), "2".to_string()]))
+            .finish();
+        assert_eq!(json, "{\"type\":\"ruSt\",\"length\":5,\"critical\":false,\"list\":[1,2]}");
+    }
+
+    #[test]
+    fn test_error() {
+        let err: pngme::Error = Box::new(ChunkError::InvalidCrc(1, 2));
+        let json = error(err.as_ref());
+        assert!(json.starts_with("{\"error\":\"InvalidCrc\",\"kind\":\"ChunkError\""));
+        assert!(json.ends_with("\"details\":{\"expected\":1,\"actual\":2}}"));
+    }
+
+    #[test]
+    fn test_error_variant_names() {
+        let err: pngme::Error = Box::new(PngError::IhdrNotFirst);
+        assert!(error(err.as_ref()).starts_with("{\"error\":\"IhdrNotFirst\",\"kind\":\"PngError\""));
+
+        let err: pngme::Error = Box::new(ChunkError::UnexpectedEof { expected: 4, got: 1 });
+        assert!(error(err.as_ref()).starts_with("{\"error\":\"UnexpectedEof\",\"kind\":\"ChunkError\""));
+
+        let err: pngme::Error = Box::new(std::io::Error::new(std::io::ErrorKind::NotFound, "missing"));
+        assert!(error(err.as_ref()).starts_with("{\"error\":\"NotFound\",\"kind\":\"IoError\""));
+    }
+}
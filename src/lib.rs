@@ -0,0 +1,15 @@
+pub mod chunk;
+pub mod chunk_type;
+pub mod color;
+pub mod errors;
+pub mod framing;
+pub mod ihdr;
+pub mod in_memory;
+pub mod metadata;
+pub mod png;
+pub mod text_encoding;
+
+pub use in_memory::{decode_in_memory, encode_in_memory};
+
+pub type Error = Box<dyn std::error::Error>;
+pub type Result<T> = std::result::Result<T, Error>;
@@ -1,12 +1,41 @@
-// mod args;
-mod chunk;
-mod chunk_type;
-// mod commands;
-mod png;
+mod args;
+mod commands;
+mod hex;
+mod json;
+mod style;
 
-pub type Error = Box<dyn std::error::Error>;
-pub type Result<T> = std::result::Result<T, Error>;
+use clap::Parser;
+use pngme::Result;
 
-fn main() -> Result<()> {
-    todo!()
+use crate::args::{Cli, GlobalArgs, PngMeArgs};
+
+fn run(command: PngMeArgs, global: &GlobalArgs) -> Result<()> {
+    match command {
+        PngMeArgs::Encode(args) => commands::encode(args, global),
+        PngMeArgs::BatchEncode(args) => commands::batch_encode(args, global),
+        PngMeArgs::Decode(args) => commands::decode(args, global),
+        PngMeArgs::Remove(args) => commands::remove(args, global),
+        PngMeArgs::Print(args) => commands::print_chunks(args, global),
+        PngMeArgs::Dump(args) => commands::dump(args, global),
+        PngMeArgs::ChunkInfo(args) => commands::chunk_info(args, global),
+        PngMeArgs::Validate(args) => commands::validate(args, global),
+        PngMeArgs::Strip(args) => commands::strip(args, global),
+        PngMeArgs::RenameType(args) => commands::rename_type(args, global),
+        PngMeArgs::IsPng(args) => commands::is_png(args, global),
+        PngMeArgs::SetText(args) => commands::set_text(args, global),
+        PngMeArgs::GenType(args) => commands::gen_type(args, global),
+        PngMeArgs::ListTypes => commands::list_types(global),
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli.command, &cli.global) {
+        if cli.global.json {
+            eprintln!("{}", json::error(e.as_ref()));
+        } else {
+            eprintln!("Error: {}", e);
+        }
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,193 @@
+//! The `pnGm` chunk that makes an encoded message self-describing.
+//!
+//! Its data is laid out as:
+//!
+//! | bytes | field                                                          |
+//! |-------|----------------------------------------------------------------|
+//! | 0     | format version of this layout, currently 1                     |
+//! | 1     | payload encoding: 0 plain, 1 compressed, 2 encrypted, 3 split  |
+//! | 2..6  | chunk type the message is stored under                         |
+//! | 6..   | version of pngme that wrote it, as utf-8                       |
+//!
+//! A PNG may hold one `pnGm` chunk per message chunk type.
+
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use crate::Result;
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+
+/// The format version written by this build, and the newest it can read.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// How the message in the described chunk is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayloadEncoding {
+    /// The chunk data is the message itself
+    #[default]
+    Plain,
+    Compressed,
+    Encrypted,
+    Split,
+}
+
+impl TryFrom<u8> for PayloadEncoding {
+    type Error = crate::Error;
+
+    fn try_from(byte: u8) -> Result<Self> {
+        match byte {
+            0 => Ok(PayloadEncoding::Plain),
+            1 => Ok(PayloadEncoding::Compressed),
+            2 => Ok(PayloadEncoding::Encrypted),
+            3 => Ok(PayloadEncoding::Split),
+            _ => Err(Box::from(MetadataError::UnknownEncoding(byte))),
+        }
+    }
+}
+
+impl From<PayloadEncoding> for u8 {
+    fn from(encoding: PayloadEncoding) -> u8 {
+        match encoding {
+            PayloadEncoding::Plain => 0,
+            PayloadEncoding::Compressed => 1,
+            PayloadEncoding::Encrypted => 2,
+            PayloadEncoding::Split => 3,
+        }
+    }
+}
+
+impl Display for PayloadEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PayloadEncoding::Plain => "plain",
+            PayloadEncoding::Compressed => "compressed",
+            PayloadEncoding::Encrypted => "encrypted",
+            PayloadEncoding::Split => "split",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The contents of a `pnGm` chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadInfo {
+    pub format_version: u8,
+    pub encoding: PayloadEncoding,
+    pub chunk_type: ChunkType,
+    pub tool_version: String,
+}
+
+impl PayloadInfo {
+    /// The chunk type metadata is stored under.
+    pub const CHUNK_TYPE: [u8; 4] = *b"pnGm";
+
+    /// Describes a message stored under `chunk_type` by this build of pngme.
+    pub fn new(chunk_type: ChunkType, encoding: PayloadEncoding) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            encoding,
+            chunk_type,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// Builds the `pnGm` chunk holding this metadata.
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        let mut data = Vec::with_capacity(6 + self.tool_version.len());
+        data.push(self.format_version);
+        data.push(u8::from(self.encoding));
+        data.extend_from_slice(self.chunk_type.as_ref());
+        data.extend_from_slice(self.tool_version.as_bytes());
+        Ok(Chunk::new(ChunkType::try_from(PayloadInfo::CHUNK_TYPE)?, data))
+    }
+
+    /// Reads the metadata back from a `pnGm` chunk. Errors for any other
+    /// chunk type, for data shorter than 6 bytes and for format versions
+    /// newer than `FORMAT_VERSION`.
+    pub fn from_chunk(chunk: &Chunk) -> Result<Self> {
+        if chunk.chunk_type().bytes() != PayloadInfo::CHUNK_TYPE {
+            return Err(Box::from(MetadataError::NotMetadata(*chunk.chunk_type())));
+        }
+        let data = chunk.data();
+        if data.len() < 6 {
+            return Err(Box::from(MetadataError::TooShort(data.len())));
+        }
+        if data[0] > FORMAT_VERSION {
+            return Err(Box::from(MetadataError::UnsupportedVersion(data[0])));
+        }
+        Ok(Self {
+            format_version: data[0],
+            encoding: PayloadEncoding::try_from(data[1])?,
+            chunk_type: ChunkType::try_from([data[2], data[3], data[4], data[5]])?,
+            tool_version: std::str::from_utf8(&data[6..])?.to_string(),
+        })
+    }
+}
+
+// Metadata Errors
+#[derive(Debug)]
+pub enum MetadataError {
+    // Chunk is not a pnGm chunk
+    NotMetadata(ChunkType),
+
+    // Data is shorter than the 6 byte fixed part (actual length)
+    TooShort(usize),
+
+    // Written with a newer layout than this build understands
+    UnsupportedVersion(u8),
+
+    // Payload encoding byte is not one of the defined values
+    UnknownEncoding(u8),
+
+    // Message uses an encoding this build can't decode
+    UnsupportedEncoding(PayloadEncoding),
+}
+
+impl std::error::Error for MetadataError {}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataError::NotMetadata(chunk_type) => {
+                write!(f, "Chunk type {} is not a pnGm metadata chunk", chunk_type)
+            },
+            MetadataError::TooShort(length) => {
+                write!(f, "pnGm chunk data must be at least 6 bytes, got {}", length)
+            },
+            MetadataError::UnsupportedVersion(version) => {
+                write!(f, "pnGm format version {} is newer than the supported version {}", version, FORMAT_VERSION)
+            },
+            MetadataError::UnknownEncoding(byte) => {
+                write!(f, "Unknown payload encoding {}", byte)
+            },
+            MetadataError::UnsupportedEncoding(encoding) => {
+                write!(f, "Message is {}, which this build of pngme can't decode", encoding)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_payload_info_round_trip() {
+        let info = PayloadInfo::new(ChunkType::from_str("ruSt").unwrap(), PayloadEncoding::Plain);
+        let chunk = info.to_chunk().unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "pnGm");
+        assert_eq!(&chunk.data()[..6], b"\x01\x00ruSt");
+        assert_eq!(PayloadInfo::from_chunk(&chunk).unwrap(), info);
+    }
+
+    #[test]
+    fn test_payload_info_invalid() {
+        let pngm = |data: &[u8]| Chunk::new(ChunkType::from_str("pnGm").unwrap(), data.to_vec());
+        assert!(PayloadInfo::from_chunk(&pngm(b"\x01\x00ruS")).is_err());
+        assert!(PayloadInfo::from_chunk(&pngm(b"\x02\x00ruSt")).is_err());
+        assert!(PayloadInfo::from_chunk(&pngm(b"\x01\x09ruSt")).is_err());
+        assert!(PayloadInfo::from_chunk(&Chunk::new(ChunkType::from_str("ruSt").unwrap(), vec![])).is_err());
+    }
+}
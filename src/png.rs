@@ -1,20 +1,23 @@
-/// A PNG container as described by the PNG spec
-/// http://www.libpng.org/pub/png/spec/1.2/PNG-Contents.html
+//! A PNG container as described by the PNG spec
+//! http://www.libpng.org/pub/png/spec/1.2/PNG-Contents.html
 
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::Display;
 use std::fs;
-use std::io::{BufReader, Read};
+use std::io::{BufWriter, Read, Write};
 use std::path::Path;
 use std::str::FromStr;
 
 use crate::{Error, Result};
-use crate::chunk::Chunk;
+use crate::chunk::{Chunk, ChunkError, CrcMode, ParseOptions};
 use crate::chunk_type::ChunkType;
 
-#[derive(Debug)]
+/// `Png::default()` is the same as `Png::new()`.
+#[derive(Debug, PartialEq, Eq)]
 pub struct Png {
+    header: [u8; 8],
     chunks: Vec<Chunk>
 }
 
@@ -22,31 +25,320 @@ impl Png {
     // Fill in this array with the correct values per the PNG spec
     pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
 
+    /// Creates a `Png` with no chunks, to be filled with `append_chunk`.
+    /// It still serializes with the standard header, so an empty `Png` is
+    /// 8 bytes long. It is not valid until `IHDR` and `IEND` are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
     /// Creates a `Png` from a list of chunks using the correct header
     pub fn from_chunks(chunks: Vec<Chunk>) -> Self {
-        Self { chunks }
+        Self { header: Png::STANDARD_HEADER, chunks }
+    }
+
+    /// Replaces the signature written by `as_bytes`, `write_to` and friends,
+    /// e.g. to produce deliberately broken files for testing other parsers.
+    pub fn with_header(mut self, header: [u8; 8]) -> Png {
+        self.header = header;
+        self
     }
 
-    /// Appends a chunk to the end of this `Png` file's `Chunk` list.
+    /// Appends a chunk to this `Png` file's `Chunk` list, just in front of
+    /// `IEND` if there is one and at the very end otherwise.
     pub fn append_chunk(&mut self, chunk: Chunk) {
-        self.chunks.push(chunk);
+        match self.index_of_iend() {
+            Some(index) => self.chunks.insert(index, chunk),
+            None => self.chunks.push(chunk),
+        }
+    }
+
+    /// Inserts a chunk at `index`, shifting every later chunk back.
+    pub fn insert_chunk(&mut self, index: usize, chunk: Chunk) -> Result<()> {
+        if index > self.chunks.len() {
+            return Err(Box::from(PngError::IndexOutOfBounds(index, self.chunks.len())));
+        }
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+
+    /// Swaps the chunks at indices `a` and `b`. Unlike `Vec::swap` this
+    /// errors instead of panicking when either index is out of range.
+    pub fn swap_chunks(&mut self, a: usize, b: usize) -> Result<()> {
+        self.check_index(a)?;
+        self.check_index(b)?;
+        self.chunks.swap(a, b);
+        Ok(())
+    }
+
+    /// Moves the chunk at `from` so that it ends up at index `to`, shifting
+    /// the chunks in between by one. Both indices must be in range.
+    pub fn move_chunk(&mut self, from: usize, to: usize) -> Result<()> {
+        self.check_index(from)?;
+        self.check_index(to)?;
+        let chunk = self.chunks.remove(from);
+        self.chunks.insert(to, chunk);
+        Ok(())
+    }
+
+    fn check_index(&self, index: usize) -> Result<()> {
+        if index >= self.chunks.len() {
+            return Err(Box::from(PngError::IndexOutOfBounds(index, self.chunks.len())));
+        }
+        Ok(())
+    }
+
+    /// Inserts a chunk directly after the last `Chunk` with the specified `chunk_type`.
+    pub fn insert_after_type(&mut self, chunk_type_str: &str, chunk: Chunk) -> Result<()> {
+        let chunk_type = ChunkType::from_str(chunk_type_str)?;
+        let index = self.chunks
+            .iter()
+            .rposition(|c| *c.chunk_type() == chunk_type)
+            .ok_or(PngError::ChunkTypeNotFound)?;
+        self.chunks.insert(index + 1, chunk);
+        Ok(())
+    }
+
+    /// Builds a `tEXt` chunk from `keyword` and `text` and appends it before
+    /// `IEND`, even if a text chunk with the same keyword already exists.
+    pub fn insert_text(&mut self, keyword: &str, text: &str) -> Result<()> {
+        self.append_chunk(Chunk::new_text(keyword, text)?);
+        Ok(())
+    }
+
+    /// Replaces the first `tEXt` chunk with `keyword` in place and returns the
+    /// old chunk. Without such a chunk it appends like `insert_text` and returns `None`.
+    pub fn set_text(&mut self, keyword: &str, text: &str) -> Result<Option<Chunk>> {
+        let chunk = Chunk::new_text(keyword, text)?;
+        let existing = self.chunks
+            .iter()
+            .position(|c| c.as_text().is_some_and(|(k, _)| k == keyword));
+
+        match existing {
+            Some(index) => Ok(Some(std::mem::replace(&mut self.chunks[index], chunk))),
+            None => {
+                self.append_chunk(chunk);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the text of the first `tEXt` chunk with `keyword`. If there is
+    /// none, `default` is inserted like `insert_text` and returned instead.
+    pub fn get_or_insert_text(&mut self, keyword: &str, default: &str) -> Result<String> {
+        let existing = self.chunks
+            .iter()
+            .filter_map(Chunk::as_text)
+            .find(|(k, _)| k == keyword);
+
+        match existing {
+            Some((_, text)) => Ok(text),
+            None => {
+                self.insert_text(keyword, default)?;
+                Ok(default.to_string())
+            }
+        }
     }
 
     /// Searches for a `Chunk` with the specified `chunk_type` and removes the first
     /// matching `Chunk` from this `Png` list of chunks.
+    /// Critical chunks are removed like any other; only the CLI refuses them.
     pub fn remove_chunk(&mut self, chunk_type_str: &str) -> Result<Chunk> {
-        let chunk_type = ChunkType::from_str(chunk_type_str).unwrap();
-        let index = self.chunks
-            .iter()
-            .position(|c| *c.chunk_type() == chunk_type)
+        ChunkType::from_str(chunk_type_str)?;
+        let index = self
+            .position_of_type(chunk_type_str)
             .ok_or(PngError::ChunkTypeNotFound)?;
         let removed = self.chunks.remove(index);
         Ok(removed)
     }
 
-    /// The header of this PNG.
+    /// Removes every `Chunk` with the specified `chunk_type` and returns them in order.
+    pub fn remove_all_chunks(&mut self, chunk_type_str: &str) -> Result<Vec<Chunk>> {
+        let chunk_type = ChunkType::from_str(chunk_type_str)?;
+        let (removed, kept) = self.chunks
+            .drain(..)
+            .partition(|c| *c.chunk_type() == chunk_type);
+        self.chunks = kept;
+
+        if removed.is_empty() {
+            return Err(Box::from(PngError::ChunkTypeNotFound));
+        }
+        Ok(removed)
+    }
+
+    /// Changes every `Chunk` of type `from` to type `to` in place, keeping
+    /// its data and position and recomputing its CRC. Both types are checked
+    /// before any chunk is touched. Returns how many chunks were renamed.
+    pub fn rename_chunk_type(&mut self, from: &str, to: &str) -> Result<usize> {
+        let from = ChunkType::from_str(from)?;
+        let to = ChunkType::from_str(to)?;
+        let mut renamed = 0;
+        for chunk in self.chunks.iter_mut().filter(|c| *c.chunk_type() == from) {
+            chunk.set_chunk_type(to);
+            renamed += 1;
+        }
+
+        if renamed == 0 {
+            return Err(Box::from(PngError::ChunkTypeNotFound));
+        }
+        Ok(renamed)
+    }
+
+    /// Counts the chunks with the specified `chunk_type`. A malformed type matches nothing.
+    pub fn count_of_type(&self, chunk_type_str: &str) -> usize {
+        match ChunkType::from_str(chunk_type_str) {
+            Ok(chunk_type) => self.chunks.iter().filter(|c| *c.chunk_type() == chunk_type).count(),
+            Err(_) => 0,
+        }
+    }
+
+    /// Counts the chunks of every type present, ordered by chunk type.
+    pub fn chunk_count_by_type(&self) -> BTreeMap<ChunkType, usize> {
+        let mut counts = BTreeMap::new();
+        for chunk in &self.chunks {
+            *counts.entry(*chunk.chunk_type()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Replaces the data of the first `Chunk` with the specified `chunk_type`,
+    /// keeping its position, and returns the old `Chunk`.
+    pub fn replace_first_chunk(&mut self, chunk_type_str: &str, new_data: Vec<u8>) -> Result<Chunk> {
+        let chunk_type = ChunkType::from_str(chunk_type_str)?;
+        let index = self
+            .position_of_type(chunk_type_str)
+            .ok_or(PngError::ChunkTypeNotFound)?;
+        let replaced = std::mem::replace(&mut self.chunks[index], Chunk::new(chunk_type, new_data));
+        Ok(replaced)
+    }
+
+    /// Calls `f` with the type and data of every `Chunk` in order. When `f`
+    /// returns `Some(data)` that chunk's data is replaced, which also updates
+    /// its CRC; `None` leaves the chunk untouched. Returns how many chunks changed.
+    pub fn map_chunk_data<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&ChunkType, &[u8]) -> Option<Vec<u8>>,
+    {
+        let mut changed = 0;
+        for chunk in &mut self.chunks {
+            if let Some(data) = f(chunk.chunk_type(), chunk.data()) {
+                chunk.set_data(data);
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Removes every `Chunk` that has the same type and data as an earlier one,
+    /// keeping the first occurrence and the order of the rest.
+    /// Returns how many chunks were removed.
+    pub fn deduplicate_chunks(&mut self) -> usize {
+        let before = self.chunks.len();
+        let mut kept: Vec<Chunk> = Vec::with_capacity(before);
+        for chunk in self.chunks.drain(..) {
+            if !kept.contains(&chunk) {
+                kept.push(chunk);
+            }
+        }
+        self.chunks = kept;
+        before - self.chunks.len()
+    }
+
+    /// Removes every `Chunk` of `chunk_type` but the first, which keeps its
+    /// position. Unlike `deduplicate_chunks` the removed chunks don't have to
+    /// match the first one, e.g. for conflicting `gAMA` chunks.
+    /// Returns how many chunks were removed.
+    pub fn ensure_single(&mut self, chunk_type: &str) -> Result<usize> {
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        let before = self.chunks.len();
+        let mut seen = false;
+        self.chunks.retain(|c| {
+            if *c.chunk_type() != chunk_type {
+                return true;
+            }
+            !std::mem::replace(&mut seen, true)
+        });
+        Ok(before - self.chunks.len())
+    }
+
+    /// Removes every ancillary chunk, keeping only the critical ones such as
+    /// `IHDR`, `PLTE`, `IDAT` and `IEND`. Critical chunks of unknown types,
+    /// private ones included, are kept as well. Returns how many chunks were removed.
+    pub fn strip_metadata(&mut self) -> usize {
+        let before = self.chunks.len();
+        self.chunks.retain(|c| c.chunk_type().is_critical());
+        before - self.chunks.len()
+    }
+
+    /// The compressed image stream: the data of every `IDAT` chunk joined in
+    /// chunk order, since one stream may be split across many `IDAT`s.
+    /// Empty if there are no `IDAT` chunks.
+    pub fn idat_data(&self) -> Vec<u8> {
+        self.chunks
+            .iter()
+            .filter(|c| c.chunk_type().bytes() == *b"IDAT")
+            .flat_map(|c| c.data().iter().copied())
+            .collect()
+    }
+
+    /// Compares only the `IHDR`, `PLTE` and `IDAT` chunks of both images, in
+    /// order, so two files that differ only in ancillary chunks, unknown
+    /// critical chunks or `IEND` are considered the same image.
+    pub fn same_image(&self, other: &Png) -> bool {
+        fn image_chunks(png: &Png) -> impl Iterator<Item = &Chunk> {
+            png.chunks
+                .iter()
+                .filter(|c| matches!(&c.chunk_type().bytes(), b"IHDR" | b"PLTE" | b"IDAT"))
+        }
+        image_chunks(self).eq(image_chunks(other))
+    }
+
+    /// Returns the index of the first `Chunk` with the specified `chunk_type`,
+    /// or `None` if there is no such chunk or `chunk_type_str` is malformed.
+    pub fn position_of_type(&self, chunk_type_str: &str) -> Option<usize> {
+        let chunk_type = ChunkType::from_str(chunk_type_str).ok()?;
+        self.chunks
+            .iter()
+            .position(|c| *c.chunk_type() == chunk_type)
+    }
+
+    /// Returns whether any `Chunk` has the specified `chunk_type`.
+    /// A malformed `chunk_type_str` matches nothing.
+    pub fn contains_type(&self, chunk_type_str: &str) -> bool {
+        self.position_of_type(chunk_type_str).is_some()
+    }
+
+    /// Returns the index of the `IEND` chunk, or `None` if there isn't one.
+    /// Same as `index_of_iend`.
+    pub fn position_of_iend(&self) -> Option<usize> {
+        self.index_of_iend()
+    }
+
+    /// Returns the index of the first `IHDR` chunk, or `None` if there isn't one.
+    pub fn index_of_ihdr(&self) -> Option<usize> {
+        self.index_of(b"IHDR")
+    }
+
+    /// Returns the index of the first `IDAT` chunk, or `None` if there isn't one.
+    pub fn index_of_first_idat(&self) -> Option<usize> {
+        self.index_of(b"IDAT")
+    }
+
+    /// Returns the index of the first `IEND` chunk, or `None` if there isn't one.
+    pub fn index_of_iend(&self) -> Option<usize> {
+        self.index_of(b"IEND")
+    }
+
+    fn index_of(&self, chunk_type: &[u8; 4]) -> Option<usize> {
+        self.chunks
+            .iter()
+            .position(|c| c.chunk_type().bytes_ref() == chunk_type)
+    }
+
+    /// The header of this PNG: the standard header, the signature found by
+    /// a lenient parse, or the one set with `with_header`.
     pub fn header(&self) -> &[u8; 8] {
-        &Png::STANDARD_HEADER
+        &self.header
     }
 
     /// Lists the `Chunk`s stored in this `Png`
@@ -54,35 +346,469 @@ impl Png {
         &self.chunks
     }
 
+    /// Consumes this `Png` and returns its `Chunk`s, for changes the other
+    /// methods don't cover. Rebuilding with `from_chunks` doesn't re-validate
+    /// the structure, so call `validate` on the result if that matters.
+    pub fn take_chunks(self) -> Vec<Chunk> {
+        self.chunks
+    }
+
+    /// The type of every `Chunk` in order, as strings.
+    pub fn chunk_types_str(&self) -> Vec<String> {
+        self.chunks.iter().map(|c| c.chunk_type().to_string()).collect()
+    }
+
+    /// The `Chunk` at `index`, or `None` if `index` is out of bounds.
+    pub fn chunk_at(&self, index: usize) -> Option<&Chunk> {
+        self.chunks.get(index)
+    }
+
+    /// A mutable reference to the `Chunk` at `index`, or `None` if `index` is out of bounds.
+    pub fn chunk_at_mut(&mut self, index: usize) -> Option<&mut Chunk> {
+        self.chunks.get_mut(index)
+    }
+
+    /// The first `Chunk` of this `Png`, or `None` if it has no chunks.
+    pub fn first_chunk(&self) -> Option<&Chunk> {
+        self.chunks.first()
+    }
+
+    /// The last `Chunk` of this `Png`, or `None` if it has no chunks.
+    pub fn last_chunk(&self) -> Option<&Chunk> {
+        self.chunks.last()
+    }
+
     /// Searches for a `Chunk` with the specified `chunk_type` and returns the first
-    /// matching `Chunk` from this `Png`.
+    /// matching `Chunk` from this `Png`, or `None` if `chunk_type_str` is malformed.
     pub fn chunk_by_type(&self, chunk_type_str: &str) -> Option<&Chunk> {
-        let chunk_type = ChunkType::from_str(chunk_type_str).unwrap();
-        for chunk in &self.chunks {
-            if *chunk.chunk_type() == chunk_type {
-                return Some(chunk);
-            }
-        }
-        None
+        self.position_of_type(chunk_type_str).map(|index| &self.chunks[index])
+    }
+
+    /// The data of the first `Chunk` with the specified `chunk_type`, or `None`
+    /// if there is no such chunk or `chunk_type_str` is malformed.
+    pub fn chunk_data_by_type(&self, chunk_type_str: &str) -> Option<&[u8]> {
+        self.chunk_by_type(chunk_type_str).map(Chunk::data)
     }
 
     /// Returns this `Png` as a byte sequence.
     /// These bytes will contain the header followed by the bytes of all of the chunks.
     pub fn as_bytes(&self) -> Vec<u8> {
-       let header: Vec<u8> = self.header().iter().copied().collect();
-       let bytes: Vec<u8> = self.chunks
-                    .iter()
-                    .flat_map(|c| c.as_bytes().into_iter())
-                    .collect();
+        let mut bytes = Vec::with_capacity(self.total_size());
+        bytes.extend_from_slice(self.header());
+        for chunk in &self.chunks {
+            bytes.extend(chunk.as_bytes());
+        }
+        bytes
+    }
 
-        header.into_iter().chain(bytes.into_iter()).collect()
+    /// Writes the Display layout of this `Png`, formatting each chunk with `write_chunk`.
+    pub fn write_with<W, F>(&self, out: &mut W, mut write_chunk: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        F: FnMut(&mut W, &Chunk) -> fmt::Result,
+    {
+        writeln!(out, "Png {{",)?;
+        for chunk in &self.chunks {
+            write_chunk(out, chunk)?;
+            write!(out, " ")?;
+        }
+        writeln!(out)?;
+        writeln!(out, "}}",)?;
+        Ok(())
     }
+
+    /// The number of bytes `as_bytes` produces: the header plus every serialized chunk.
+    pub fn total_size(&self) -> usize {
+        self.header().len() + self.chunks.iter().map(Chunk::serialized_len).sum::<usize>()
+    }
+
+    /// Iterates over every `Chunk` together with its byte offset within
+    /// `as_bytes`, i.e. where its length field starts. Offsets are the running
+    /// total of serialized lengths after the 8 byte header.
+    pub fn iter_with_offsets(&self) -> impl Iterator<Item = (usize, &Chunk)> {
+        self.chunks.iter().scan(self.header().len(), |offset, chunk| {
+            let start = *offset;
+            *offset += chunk.serialized_len();
+            Some((start, chunk))
+        })
+    }
+
+    /// The byte offset of every `Chunk` within `as_bytes`, as in `iter_with_offsets`.
+    pub fn chunk_offsets(&self) -> Vec<usize> {
+        self.iter_with_offsets().map(|(offset, _)| offset).collect()
+    }
+
+    /// Reads at most the first 8 bytes from `reader` and reports whether they
+    /// are the standard PNG signature. Shorter input is not a PNG.
+    pub fn starts_with_signature<R: Read>(reader: R) -> Result<bool> {
+        let mut header = Vec::with_capacity(Png::STANDARD_HEADER.len());
+        reader
+            .take(Png::STANDARD_HEADER.len() as u64)
+            .read_to_end(&mut header)?;
+        Ok(header == Png::STANDARD_HEADER)
+    }
+
+    /// Reads a `Png` from `reader` one chunk at a time until the stream ends,
+    /// checking the signature and every chunk like `TryFrom<&[u8]>`. A stream
+    /// that ends between chunks is a complete `Png`; one that ends partway
+    /// through a chunk errors with `ChunkError::UnexpectedEof`.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Png> {
+        let mut header = [0; 8];
+        if let Err(e) = reader.read_exact(&mut header) {
+            if e.kind() == std::io::ErrorKind::UnexpectedEof {
+                return Err(Box::from(PngError::InputTooSmall));
+            }
+            return Err(Box::from(e));
+        }
+        if header != Png::STANDARD_HEADER {
+            return Err(Box::from(PngError::InvaldPngHeader));
+        }
+
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("png_from_reader").entered();
+        #[cfg(feature = "trace")]
+        let mut offset = Png::STANDARD_HEADER.len();
+
+        let mut chunks = vec![];
+        loop {
+            #[cfg(feature = "trace")]
+            let span = chunk_span(offset);
+            #[cfg(feature = "trace")]
+            let _entered = span.enter();
+
+            let chunk = Chunk::from_reader(&mut reader);
+            #[cfg(feature = "trace")]
+            trace_chunk(&span, &chunk);
+            match chunk {
+                Ok(chunk) => {
+                    #[cfg(feature = "trace")]
+                    {
+                        offset += chunk.serialized_len();
+                    }
+                    chunks.push(chunk)
+                }
+                Err(e) if matches!(e.downcast_ref::<ChunkError>(), Some(ChunkError::Eof)) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Png::from_chunks(chunks))
+    }
+
+    /// Reads and parses the file at `path` like `TryFrom<&[u8]>`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Png> {
+        let bytes = fs::read(path)?;
+        Png::try_from(bytes.as_slice())
+    }
+
+    /// Parses a `Png` according to `options`.
+    /// Lenient parsing accepts any 8 byte signature and ignores anything after
+    /// `IEND`. Strict parsing requires the standard signature, consumes every
+    /// byte and runs `validate` on the result.
+    pub fn parse(bytes: &[u8], options: &ParseOptions) -> Result<Png> {
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("png_parse", len = bytes.len(), strict = options.strict).entered();
+
+        if bytes.len() < Png::STANDARD_HEADER.len() {
+            return Err(Box::from(PngError::InputTooSmall));
+        }
+
+        let (header, mut rest) = bytes.split_at(Png::STANDARD_HEADER.len());
+        if options.strict && header != Png::STANDARD_HEADER {
+            return Err(Box::from(PngError::InvaldPngHeader));
+        }
+
+        let mut chunks: Vec<Chunk> = vec![];
+        while !rest.is_empty() {
+            let after_iend = chunks
+                .last()
+                .is_some_and(|c| c.chunk_type().bytes() == *b"IEND");
+            if after_iend && !options.strict {
+                break;
+            }
+
+            #[cfg(feature = "trace")]
+            let span = chunk_span(bytes.len() - rest.len());
+            #[cfg(feature = "trace")]
+            let _entered = span.enter();
+
+            let chunk = Chunk::parse(rest, options);
+            #[cfg(feature = "trace")]
+            trace_chunk(&span, &chunk);
+            let chunk = chunk?;
+
+            rest = &rest[chunk.serialized_len()..];
+            chunks.push(chunk);
+        }
+
+        let png = Png { header: <[u8; 8]>::try_from(header)?, chunks };
+        if options.strict {
+            png.validate()?;
+        }
+        Ok(png)
+    }
+
+    /// Reorders the chunks into a canonical order. Refuses with the error of
+    /// `validate`, leaving the chunks untouched, unless this `Png` is valid;
+    /// `IHDR` and `IEND` keep their places, so the result is valid too.
+    ///
+    /// The order is: `IHDR`; `cHRM`, `gAMA`, `iCCP`, `sBIT` and `sRGB`, which
+    /// must come before `PLTE`; `PLTE`; `tRNS`, `bKGD`, `hIST`, `pHYs`, `sPLT`
+    /// and `eXIf`, which must come between `PLTE` and `IDAT`; the free-standing
+    /// `tEXt`, `zTXt`, `iTXt` and `tIME`; every `IDAT` as one run; `IEND`.
+    /// The sort is stable, so chunks in the same group and the `IDAT` chunks
+    /// keep their original order. Chunks of any other type may depend on their
+    /// position relative to the known chunks, so each one stays in the group of
+    /// the last known chunk before it, after that group's known chunks. This
+    /// moves unknown chunks found between `IDAT`s behind the whole `IDAT` run.
+    pub fn sort_chunks_canonical(&mut self) -> Result<()> {
+        self.validate()?;
+
+        // sort key: (rank of the group, whether the chunk type is unknown)
+        let mut rank = 0;
+        let mut keyed: Vec<((u8, bool), Chunk)> = self.chunks
+            .drain(..)
+            .map(|chunk| match canonical_rank(chunk.chunk_type()) {
+                Some(known) => {
+                    rank = known;
+                    ((rank, false), chunk)
+                }
+                None => ((rank, true), chunk),
+            })
+            .collect();
+        keyed.sort_by_key(|(key, _)| *key);
+        self.chunks = keyed.into_iter().map(|(_, chunk)| chunk).collect();
+        Ok(())
+    }
+
+    /// Checks the structural rules every PNG must follow: `IHDR` is the
+    /// first chunk, `IEND` is the last, and neither they nor `PLTE` appear
+    /// more than once. A repeated type is reported at its first duplicate.
+    pub fn validate(&self) -> Result<()> {
+        match self.first_chunk() {
+            Some(chunk) if chunk.chunk_type().bytes() == *b"IHDR" => {}
+            _ => return Err(Box::from(PngError::IhdrNotFirst)),
+        }
+        match self.last_chunk() {
+            Some(chunk) if chunk.chunk_type().bytes() == *b"IEND" => {}
+            _ => return Err(Box::from(PngError::IendNotLast)),
+        }
+
+        let mut seen = [0usize; 3];
+        for chunk in &self.chunks {
+            let slot = match &chunk.chunk_type().bytes() {
+                b"IHDR" => 0,
+                b"PLTE" => 1,
+                b"IEND" => 2,
+                _ => continue,
+            };
+            seen[slot] += 1;
+            if seen[slot] > 1 {
+                return Err(Box::from(PngError::DuplicateChunk(*chunk.chunk_type())));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the placement rules the spec sets for known chunks, on top of
+    /// what `validate` covers, and reports the first chunk that breaks one:
+    ///
+    /// - `cHRM`, `gAMA`, `iCCP`, `sBIT` and `sRGB` come before `PLTE` and `IDAT`
+    /// - `PLTE`, `pHYs` and `sPLT` come before `IDAT`
+    /// - `bKGD`, `hIST` and `tRNS` come before `IDAT`, and after `PLTE` if there is one
+    /// - `IDAT` chunks are consecutive
+    ///
+    /// Chunks of any other type, including `tEXt`, `zTXt`, `iTXt` and `tIME`,
+    /// may appear anywhere and are not checked.
+    pub fn validate_ordering(&self) -> Result<()> {
+        let has_plte = self.contains_type("PLTE");
+        let first_idat = self.index_of_first_idat();
+        let mut seen_plte = false;
+        let mut idat_ended = false;
+
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let seen_idat = first_idat.is_some_and(|first| index > first);
+            let misplaced = |reason| Err(Box::from(PngError::MisorderedChunk(*chunk.chunk_type(), reason)));
+            match &chunk.chunk_type().bytes() {
+                b"cHRM" | b"gAMA" | b"iCCP" | b"sBIT" | b"sRGB" if seen_plte => {
+                    return misplaced("must come before PLTE");
+                }
+                b"bKGD" | b"hIST" | b"tRNS" if has_plte && !seen_plte => {
+                    return misplaced("must come after PLTE");
+                }
+                b"cHRM" | b"gAMA" | b"iCCP" | b"sBIT" | b"sRGB" | b"PLTE" | b"pHYs" | b"sPLT" | b"bKGD"
+                | b"hIST" | b"tRNS" if seen_idat => {
+                    return misplaced("must come before IDAT");
+                }
+                b"IDAT" if idat_ended => return misplaced("IDAT chunks must be consecutive"),
+                _ => {}
+            }
+
+            match &chunk.chunk_type().bytes() {
+                b"PLTE" => seen_plte = true,
+                b"IDAT" => {}
+                _ if seen_idat => idat_ended = true,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the CRC each chunk was parsed with against its contents and
+    /// returns the index and error of every mismatch, in order. Only chunks
+    /// parsed without CRC verification can fail; chunks built or modified in
+    /// memory have no stored CRC and always pass.
+    pub fn validate_crcs(&self) -> Vec<(usize, ChunkError)> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, chunk)| {
+                let stored = chunk.stored_crc()?;
+                let actual = chunk.crc();
+                (stored != actual).then_some((index, ChunkError::InvalidCrc(stored, actual)))
+            })
+            .collect()
+    }
+
+    /// Writes the header followed by every chunk of this `Png` to `writer`.
+    /// Returns the number of bytes written, signature included.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<usize> {
+        self.write_to_with(writer, CrcMode::Recompute)
+    }
+
+    /// Like `write_to`, but `mode` decides whether chunks parsed from bytes
+    /// keep their original CRCs, so even wrong CRCs round-trip exactly.
+    pub fn write_to_with<W: Write>(&self, writer: &mut W, mode: CrcMode) -> Result<usize> {
+        writer.write_all(self.header())?;
+        let mut written = self.header().len();
+        for chunk in &self.chunks {
+            written += chunk.write_to_with(writer, mode)?;
+        }
+        Ok(written)
+    }
+
+    /// Writes the `Png` to a file at `path`, creating or truncating it. The
+    /// chunks are streamed through a buffered writer with `write_to`, so the
+    /// whole file is never built in memory.
+    pub fn write_path<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Incrementally assembles a `Png`, keeping track of where `IEND` sits so
+/// that pushed chunks always land in front of it.
+#[derive(Debug, Default)]
+pub struct PngBuilder {
+    chunks: Vec<Chunk>,
+    iend: Option<usize>,
+    header: Option<[u8; 8]>,
+}
+
+impl PngBuilder {
+    /// Creates a builder with no chunks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a builder that continues from the chunks of an existing `Png`.
+    pub fn from_png(png: Png) -> Self {
+        let iend = png.index_of_iend();
+        Self { chunks: png.chunks, iend, header: Some(png.header) }
+    }
+
+    /// Pushes a chunk in front of `IEND`, or at the end if there is no `IEND` yet.
+    /// An `IHDR` chunk is only accepted as the very first chunk.
+    pub fn push_chunk(&mut self, chunk: Chunk) -> Result<&mut Self> {
+        let chunk_type = chunk.chunk_type().bytes();
+
+        if chunk_type == *b"IHDR" && !self.chunks.is_empty() {
+            return Err(Box::from(PngError::IhdrNotFirst));
+        }
+
+        match self.iend {
+            Some(_) if chunk_type == *b"IEND" => {
+                return Err(Box::from(PngError::DuplicateChunk(*chunk.chunk_type())));
+            }
+            Some(index) => {
+                self.chunks.insert(index, chunk);
+                self.iend = Some(index + 1);
+            }
+            None => {
+                if chunk_type == *b"IEND" {
+                    self.iend = Some(self.chunks.len());
+                }
+                self.chunks.push(chunk);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Consumes the builder and returns the assembled `Png`.
+    pub fn finish(self) -> Png {
+        let png = Png::from_chunks(self.chunks);
+        match self.header {
+            Some(header) => png.with_header(header),
+            None => png,
+        }
+    }
+
+    /// Consumes the builder and writes the assembled `Png` straight to `writer`.
+    /// Returns the number of bytes written.
+    pub fn write_to<W: Write>(self, writer: &mut W) -> Result<usize> {
+        self.finish().write_to(writer)
+    }
+}
+
+// A span for the chunk starting at `offset`. Its type and length are only
+// known once the chunk is parsed, so they start empty.
+#[cfg(feature = "trace")]
+fn chunk_span(offset: usize) -> tracing::Span {
+    tracing::trace_span!(
+        "chunk",
+        offset,
+        chunk_type = tracing::field::Empty,
+        length = tracing::field::Empty,
+    )
+}
+
+// Records the type and length of a parsed chunk on its span, or reports
+// why it failed to parse. A clean end of stream is not a failure.
+#[cfg(feature = "trace")]
+fn trace_chunk(span: &tracing::Span, chunk: &Result<Chunk>) {
+    match chunk {
+        Ok(chunk) => {
+            span.record("chunk_type", tracing::field::display(chunk.chunk_type()));
+            span.record("length", chunk.length());
+        }
+        Err(e) if matches!(e.downcast_ref::<ChunkError>(), Some(ChunkError::Eof)) => {}
+        Err(e) => tracing::debug!(error = %e, "failed to parse chunk"),
+    }
+}
+
+// Position of a known chunk type in the order of `sort_chunks_canonical`
+fn canonical_rank(chunk_type: &ChunkType) -> Option<u8> {
+    let rank = match &chunk_type.bytes() {
+        b"IHDR" => 0,
+        b"cHRM" | b"gAMA" | b"iCCP" | b"sBIT" | b"sRGB" => 1,
+        b"PLTE" => 2,
+        b"tRNS" | b"bKGD" | b"hIST" | b"pHYs" | b"sPLT" | b"eXIf" => 3,
+        b"tEXt" | b"zTXt" | b"iTXt" | b"tIME" => 4,
+        b"IDAT" => 5,
+        b"IEND" => 6,
+        _ => return None,
+    };
+    Some(rank)
 }
 
 impl TryFrom<&[u8]> for Png {
     type Error = Error;
 
     fn try_from(bytes: &[u8]) -> Result<Png> {
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("png_try_from", len = bytes.len()).entered();
+
         // Assert minimum length (header)
         if bytes.len() < Png::STANDARD_HEADER.len() {
             return Err(Box::from(PngError::InputTooSmall));
@@ -90,10 +816,8 @@ impl TryFrom<&[u8]> for Png {
 
         // validate header
         let png_header = &bytes[0..8];
-        for i in 0..8 {
-            if png_header[i] != Png::STANDARD_HEADER[i] {
-                return Err(Box::from(PngError::InvaldPngHeader));
-            }
+        if png_header != Png::STANDARD_HEADER {
+            return Err(Box::from(PngError::InvaldPngHeader));
         }
 
         // start at chunks after standard header length
@@ -103,24 +827,33 @@ impl TryFrom<&[u8]> for Png {
 
         while index < bytes.len() {
             let cur_bytes = &bytes[index..];
-            let cur_chunk = Chunk::try_from(cur_bytes)?;
 
-            index += cur_chunk.length() + Chunk::MINIUMUM_LENGTH;
+            #[cfg(feature = "trace")]
+            let span = chunk_span(index);
+            #[cfg(feature = "trace")]
+            let _entered = span.enter();
+
+            let cur_chunk = Chunk::try_from(cur_bytes);
+            #[cfg(feature = "trace")]
+            trace_chunk(&span, &cur_chunk);
+            let cur_chunk = cur_chunk?;
+
+            index += cur_chunk.serialized_len();
             chunks.push(cur_chunk);
         }
-        Ok(Png { chunks })
+        Ok(Png::from_chunks(chunks))
+    }
+}
+
+impl Default for Png {
+    fn default() -> Self {
+        Png::from_chunks(vec![])
     }
 }
 
 impl Display for Png {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Png {{",)?;
-        for chunk in self.chunks() {
-            write!(f, "{} ", chunk);
-        }
-        writeln!(f, "");
-        writeln!(f, "}}",)?;
-        Ok(())
+        self.write_with(f, |f, chunk| write!(f, "{}", chunk))
     }
 }
 
@@ -134,7 +867,22 @@ pub enum PngError {
     InputTooSmall,
 
     // Png Header does not match Standard Header
-    InvaldPngHeader
+    InvaldPngHeader,
+
+    // Chunk index is past the end of the Png (index, number of chunks)
+    IndexOutOfBounds(usize, usize),
+
+    // IHDR must be the first Chunk of a Png
+    IhdrNotFirst,
+
+    // IEND must be the last Chunk of a Png
+    IendNotLast,
+
+    // Png contains more than one Chunk of a type that may only appear once
+    DuplicateChunk(ChunkType),
+
+    // Chunk breaks a placement rule of the spec (chunk type, rule)
+    MisorderedChunk(ChunkType, &'static str),
 }
 
 impl std::error::Error for PngError {}
@@ -149,7 +897,22 @@ impl Display for PngError {
                 write!(f, "Input PNG too small!")
             },
             &PngError::InvaldPngHeader => {
-                write!(f, "Invalid PNG header! PNG bytes do not match standard header")
+                write!(f, "Not a PNG file (bad signature)")
+            },
+            PngError::IndexOutOfBounds(index, len) => {
+                write!(f, "Chunk index {} is out of bounds for a PNG with {} chunks!", index, len)
+            },
+            PngError::IhdrNotFirst => {
+                write!(f, "IHDR must be the first chunk of a PNG!")
+            },
+            PngError::IendNotLast => {
+                write!(f, "IEND must be the last chunk of a PNG!")
+            },
+            PngError::DuplicateChunk(chunk_type) => {
+                write!(f, "Duplicate {} chunk! It may only appear once", chunk_type)
+            },
+            PngError::MisorderedChunk(chunk_type, rule) => {
+                write!(f, "Misplaced {} chunk: {}", chunk_type, rule)
             }
         }
     }
@@ -164,13 +927,11 @@ mod tests {
     use std::convert::TryFrom;
 
     fn testing_chunks() -> Vec<Chunk> {
-        let mut chunks = Vec::new();
-
-        chunks.push(chunk_from_strings("FrSt", "I am the first chunk").unwrap());
-        chunks.push(chunk_from_strings("miDl", "I am another chunk").unwrap());
-        chunks.push(chunk_from_strings("LASt", "I am the last chunk").unwrap());
-
-        chunks
+        vec![
+            chunk_from_strings("FrSt", "I am the first chunk").unwrap(),
+            chunk_from_strings("miDl", "I am another chunk").unwrap(),
+            chunk_from_strings("LASt", "I am the last chunk").unwrap(),
+        ]
     }
 
     fn testing_png() -> Png {
@@ -179,8 +940,6 @@ mod tests {
     }
 
     fn chunk_from_strings(chunk_type: &str, data: &str) -> Result<Chunk> {
-        use std::str::FromStr;
-
         let chunk_type = ChunkType::from_str(chunk_type)?;
         let data: Vec<u8> = data.bytes().collect();
 
@@ -195,6 +954,15 @@ mod tests {
         assert_eq!(png.chunks().len(), 3);
     }
 
+    #[test]
+    fn test_take_chunks() {
+        let mut chunks = testing_png().take_chunks();
+        chunks.reverse();
+        let png = Png::from_chunks(chunks);
+
+        assert_eq!(png.chunk_types_str(), ["LASt", "miDl", "FrSt"]);
+    }
+
     #[test]
     fn test_valid_from_bytes() {
         let chunk_bytes: Vec<u8> = testing_chunks()
@@ -261,6 +1029,28 @@ mod tests {
         assert_eq!(chunks.len(), 3);
     }
 
+    #[test]
+    fn test_chunk_at() {
+        let mut png = testing_png();
+        assert_eq!(&png.chunk_at(1).unwrap().chunk_type().to_string(), "miDl");
+        assert!(png.chunk_at(3).is_none());
+
+        let index = png.position_of_type("LASt").unwrap();
+        assert!(png.chunk_at_mut(index).is_some());
+        assert!(png.chunk_at_mut(3).is_none());
+    }
+
+    #[test]
+    fn test_first_and_last_chunk() {
+        let png = testing_png();
+        assert_eq!(&png.first_chunk().unwrap().chunk_type().to_string(), "FrSt");
+        assert_eq!(&png.last_chunk().unwrap().chunk_type().to_string(), "LASt");
+
+        let png = Png::from_chunks(vec![]);
+        assert!(png.first_chunk().is_none());
+        assert!(png.last_chunk().is_none());
+    }
+
     #[test]
     fn test_chunk_by_type() {
         let png = testing_png();
@@ -279,6 +1069,71 @@ mod tests {
         assert_eq!(&chunk.data_as_string().unwrap(), "Message");
     }
 
+    #[test]
+    fn test_remove_all_chunks() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "Another middle").unwrap());
+        assert_eq!(png.count_of_type("miDl"), 2);
+
+        let removed = png.remove_all_chunks("miDl").unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(png.count_of_type("miDl"), 0);
+        assert_eq!(png.chunks().len(), 2);
+
+        assert!(png.remove_all_chunks("miDl").is_err());
+        assert_eq!(png.count_of_type("bad"), 0);
+    }
+
+    #[test]
+    fn test_chunk_count_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "Another middle").unwrap());
+
+        let counts: Vec<(String, usize)> = png
+            .chunk_count_by_type()
+            .into_iter()
+            .map(|(chunk_type, count)| (chunk_type.to_string(), count))
+            .collect();
+        assert_eq!(counts, [("FrSt".to_string(), 1), ("LASt".to_string(), 1), ("miDl".to_string(), 2)]);
+        assert!(Png::new().chunk_count_by_type().is_empty());
+    }
+
+    #[test]
+    fn test_rename_chunk_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "I am a second middle chunk").unwrap());
+
+        assert_eq!(png.rename_chunk_type("miDl", "nOte").unwrap(), 2);
+        assert_eq!(png.chunk_types_str(), ["FrSt", "nOte", "LASt", "nOte"]);
+        assert_eq!(png.chunk_by_type("nOte").unwrap().data_as_string().unwrap(), "I am another chunk");
+
+        assert!(png.rename_chunk_type("miDl", "nOte").is_err());
+        assert!(png.rename_chunk_type("nOte", "no7e").is_err());
+        assert_eq!(png.count_of_type("nOte"), 2);
+    }
+
+    #[test]
+    fn test_insert_chunk() {
+        let mut png = testing_png();
+        png.insert_chunk(1, chunk_from_strings("TeSt", "Message").unwrap()).unwrap();
+        assert_eq!(png.position_of_type("TeSt"), Some(1));
+
+        png.insert_chunk(4, chunk_from_strings("TeSt", "Message").unwrap()).unwrap();
+        assert_eq!(png.chunks().len(), 5);
+
+        assert!(png.insert_chunk(6, chunk_from_strings("TeSt", "Message").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_insert_after_type() {
+        let mut png = testing_png();
+        png.insert_chunk(2, chunk_from_strings("miDl", "Another middle").unwrap()).unwrap();
+        png.insert_after_type("miDl", chunk_from_strings("TeSt", "Message").unwrap()).unwrap();
+        assert_eq!(png.position_of_type("TeSt"), Some(3));
+
+        assert!(png.insert_after_type("NoNe", chunk_from_strings("TeSt", "Message").unwrap()).is_err());
+    }
+
     #[test]
     fn test_remove_chunk() {
         let mut png = testing_png();
@@ -288,20 +1143,563 @@ mod tests {
         assert!(chunk.is_none());
     }
 
+    #[test]
+    fn test_append_chunk_before_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
+        assert_eq!(png.position_of_type("TeSt"), Some(png.chunks().len() - 2));
+        assert!(png.validate().is_ok());
+    }
+
+    #[test]
+    fn test_replace_first_chunk() {
+        let mut png = testing_png();
+        let old = png.replace_first_chunk("miDl", b"Replaced".to_vec()).unwrap();
+        assert_eq!(&old.data_as_string().unwrap(), "I am another chunk");
+
+        let chunk = &png.chunks()[1];
+        assert_eq!(&chunk.chunk_type().to_string(), "miDl");
+        assert_eq!(&chunk.data_as_string().unwrap(), "Replaced");
+        assert_eq!(chunk.crc(), chunk_from_strings("miDl", "Replaced").unwrap().crc());
+
+        assert!(png.replace_first_chunk("TeSt", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_map_chunk_data() {
+        let mut png = testing_png();
+        let changed = png.map_chunk_data(|chunk_type, data| {
+            if chunk_type.is_critical() {
+                Some(data.to_ascii_uppercase())
+            } else {
+                None
+            }
+        });
+
+        assert_eq!(changed, 2);
+        assert_eq!(&png.chunks()[0].data_as_string().unwrap(), "I AM THE FIRST CHUNK");
+        assert_eq!(&png.chunks()[1].data_as_string().unwrap(), "I am another chunk");
+        assert_eq!(png.chunks()[2], chunk_from_strings("LASt", "I AM THE LAST CHUNK").unwrap());
+    }
+
+    #[test]
+    fn test_deduplicate_chunks() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("FrSt", "I am the first chunk").unwrap());
+        png.append_chunk(chunk_from_strings("FrSt", "I am a different chunk").unwrap());
+        png.append_chunk(chunk_from_strings("miDl", "I am another chunk").unwrap());
+
+        assert_eq!(png.deduplicate_chunks(), 2);
+
+        let messages: Vec<String> = png.chunks().iter().map(|c| c.data_as_string().unwrap()).collect();
+        assert_eq!(messages, vec![
+            "I am the first chunk",
+            "I am another chunk",
+            "I am the last chunk",
+            "I am a different chunk",
+        ]);
+        assert_eq!(png.deduplicate_chunks(), 0);
+    }
+
+    #[test]
+    fn test_ensure_single() {
+        let mut png = testing_png();
+        png.insert_chunk(0, chunk_from_strings("miDl", "I come first").unwrap()).unwrap();
+        png.append_chunk(chunk_from_strings("miDl", "I am a conflicting chunk").unwrap());
+
+        assert_eq!(png.ensure_single("miDl").unwrap(), 2);
+        assert_eq!(png.chunk_types_str(), ["miDl", "FrSt", "LASt"]);
+        assert_eq!(png.chunks()[0].data_as_string().unwrap(), "I come first");
+
+        assert_eq!(png.ensure_single("miDl").unwrap(), 0);
+        assert_eq!(png.ensure_single("gAMA").unwrap(), 0);
+        assert!(png.ensure_single("bad").is_err());
+    }
+
+    #[test]
+    fn test_strip_metadata() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.append_chunk(chunk_from_strings("ruSt", "Message").unwrap());
+
+        assert_eq!(png.strip_metadata(), 4);
+        assert!(png.chunks().iter().all(|c| c.chunk_type().is_critical()));
+        assert!(png.validate().is_ok());
+        assert_eq!(png.strip_metadata(), 0);
+    }
+
+    #[test]
+    fn test_position_of_type() {
+        let png = testing_png();
+        assert_eq!(png.position_of_type("FrSt"), Some(0));
+        assert_eq!(png.position_of_type("LASt"), Some(2));
+        assert_eq!(png.position_of_type("TeSt"), None);
+        assert_eq!(png.position_of_type("bad"), None);
+    }
+
+    #[test]
+    fn test_insert_text() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let count = png.chunks().len();
+        png.insert_text("Title", "Dice").unwrap();
+        png.insert_text("Title", "More dice").unwrap();
+
+        assert_eq!(png.chunks().len(), count + 2);
+        assert_eq!(png.last_chunk().unwrap().chunk_type().to_string(), "IEND");
+        assert_eq!(png.chunks()[count].as_text(), Some(("Title".to_string(), "More dice".to_string())));
+        assert!(png.insert_text(" Title", "Dice").is_err());
+    }
+
+    #[test]
+    fn test_set_text() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.set_text("Title", "Dice").unwrap().is_none());
+        let count = png.chunks().len();
+
+        let old = png.set_text("Title", "More dice").unwrap().unwrap();
+        assert_eq!(old.as_text().unwrap().1, "Dice");
+        assert_eq!(png.chunks().len(), count);
+        assert_eq!(png.chunks()[count - 2].as_text().unwrap().1, "More dice");
+    }
+
+    #[test]
+    fn test_get_or_insert_text() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let count = png.chunks().len();
+
+        assert_eq!(png.get_or_insert_text("Title", "Dice").unwrap(), "Dice");
+        assert_eq!(png.get_or_insert_text("Title", "Other").unwrap(), "Dice");
+        assert_eq!(png.chunks().len(), count + 1);
+
+        // with several matches the first one wins
+        png.insert_text("Title", "Later").unwrap();
+        assert_eq!(png.get_or_insert_text("Title", "Other").unwrap(), "Dice");
+        assert!(png.get_or_insert_text("", "Other").is_err());
+    }
+
+    #[test]
+    fn test_write_to() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut bytes = Vec::new();
+        let written = png.write_to(&mut bytes).unwrap();
+        assert_eq!(written, png.total_size());
+        assert_eq!(bytes, PNG_FILE);
+    }
+
+    #[test]
+    fn test_path_round_trip() {
+        let path = std::env::temp_dir().join(format!("pngme-path-{}.png", std::process::id()));
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.write_path(&path).unwrap();
+        let written = fs::read(&path).unwrap();
+        let read = Png::from_path(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(written, PNG_FILE);
+        assert_eq!(read.unwrap(), png);
+        assert!(Png::from_path(&path).unwrap_err().is::<std::io::Error>());
+    }
+
+    #[test]
+    fn test_write_to_with_preserved_crc() {
+        let mut bytes = PNG_FILE.to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        let options = ParseOptions { verify_crc: false, ..ParseOptions::lenient() };
+        let png = Png::parse(&bytes, &options).unwrap();
+
+        let mut preserved = Vec::new();
+        png.write_to_with(&mut preserved, CrcMode::Preserve).unwrap();
+        assert_eq!(preserved, bytes);
+
+        let mut recomputed = Vec::new();
+        png.write_to_with(&mut recomputed, CrcMode::Recompute).unwrap();
+        assert_eq!(recomputed, PNG_FILE);
+    }
+
+    #[test]
+    fn test_png_eq() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut other = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png, other);
+
+        other.insert_text("Title", "Dice").unwrap();
+        assert_ne!(png, other);
+    }
+
+    #[test]
+    fn test_same_image() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut other = Png::try_from(&PNG_FILE[..]).unwrap();
+        other.insert_text("Title", "Dice").unwrap();
+        other.strip_metadata();
+        assert!(png.same_image(&other));
+
+        let index = other.position_of_type("IDAT").unwrap();
+        other.chunk_at_mut(index).unwrap().extend_data(b"!");
+        assert!(!png.same_image(&other));
+    }
+
+    #[test]
+    fn test_chunk_offsets() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let offsets = png.chunk_offsets();
+        assert_eq!(offsets.len(), png.chunks().len());
+        assert_eq!(offsets[0], 8);
+
+        let bytes = png.as_bytes();
+        for (offset, chunk) in offsets.iter().zip(png.chunks()) {
+            assert_eq!(&bytes[*offset..*offset + chunk.serialized_len()], chunk.as_bytes().as_slice());
+        }
+    }
+
+    #[test]
+    fn test_chunk_data_by_type() {
+        let png = testing_png();
+        assert_eq!(png.chunk_data_by_type("miDl"), Some(&b"I am another chunk"[..]));
+        assert_eq!(png.chunk_data_by_type("TeSt"), None);
+        assert_eq!(png.chunk_data_by_type("bad"), None);
+        assert!(png.chunk_by_type("bad").is_none());
+    }
+
+    #[test]
+    fn test_sort_chunks_canonical() {
+        let types = ["IHDR", "tEXt", "IDAT", "prVt", "gAMA", "IDAT", "PLTE", "tRNS", "IEND"];
+        let chunks = types
+            .iter()
+            .enumerate()
+            .map(|(i, t)| chunk_from_strings(t, &i.to_string()).unwrap())
+            .collect();
+        let mut png = Png::from_chunks(chunks);
+        png.sort_chunks_canonical().unwrap();
+
+        let sorted: Vec<String> = png.chunks()
+            .iter()
+            .map(|c| format!("{}{}", c.chunk_type(), c.data_as_string().unwrap()))
+            .collect();
+        assert_eq!(
+            sorted,
+            ["IHDR0", "gAMA4", "PLTE6", "tRNS7", "tEXt1", "IDAT2", "IDAT5", "prVt3", "IEND8"]
+        );
+    }
+
+    #[test]
+    fn test_sort_chunks_canonical_invalid() {
+        let mut png = testing_png();
+        assert!(png.sort_chunks_canonical().is_err());
+        assert_eq!(png.chunks(), testing_png().chunks());
+    }
+
+    #[test]
+    fn test_new_png() {
+        let png = Png::new();
+        assert!(png.chunks().is_empty());
+        assert_eq!(png.as_bytes(), Png::STANDARD_HEADER);
+        assert_eq!(png, Png::default());
+        assert!(png.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_duplicate_chunks() {
+        for (types, duplicate) in [
+            (["IHDR", "IHDR", "IDAT", "IEND"], "IHDR"),
+            (["IHDR", "IDAT", "IEND", "IEND"], "IEND"),
+            (["IHDR", "PLTE", "PLTE", "IEND"], "PLTE"),
+        ] {
+            let chunks = types.iter().map(|t| chunk_from_strings(t, "").unwrap()).collect();
+            let err = Png::from_chunks(chunks).validate().unwrap_err();
+            match err.downcast_ref::<PngError>() {
+                Some(PngError::DuplicateChunk(chunk_type)) => assert_eq!(chunk_type.to_string(), duplicate),
+                other => panic!("unexpected error {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_ordering() {
+        let png = |types: &[&str]| {
+            Png::from_chunks(types.iter().map(|t| chunk_from_strings(t, "").unwrap()).collect())
+        };
+        assert!(png(&["IHDR", "gAMA", "PLTE", "tRNS", "IDAT", "IDAT", "tEXt", "IEND"]).validate_ordering().is_ok());
+        assert!(png(&["IHDR", "tRNS", "IDAT", "IEND"]).validate_ordering().is_ok());
+        assert!(Png::try_from(&PNG_FILE[..]).unwrap().validate_ordering().is_ok());
+
+        for (types, misplaced, rule) in [
+            (&["IHDR", "PLTE", "sRGB", "IDAT", "IEND"][..], "sRGB", "must come before PLTE"),
+            (&["IHDR", "bKGD", "PLTE", "IDAT", "IEND"][..], "bKGD", "must come after PLTE"),
+            (&["IHDR", "IDAT", "pHYs", "IEND"][..], "pHYs", "must come before IDAT"),
+            (&["IHDR", "IDAT", "tEXt", "IDAT", "IEND"][..], "IDAT", "IDAT chunks must be consecutive"),
+        ] {
+            let err = png(types).validate_ordering().unwrap_err();
+            match err.downcast_ref::<PngError>() {
+                Some(PngError::MisorderedChunk(chunk_type, reason)) => {
+                    assert_eq!((chunk_type.to_string().as_str(), *reason), (misplaced, rule));
+                }
+                other => panic!("unexpected error {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_crcs() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.validate_crcs().is_empty());
+
+        let mut bytes = PNG_FILE.to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        let options = ParseOptions { verify_crc: false, ..ParseOptions::lenient() };
+        let png = Png::parse(&bytes, &options).unwrap();
+
+        let failures = png.validate_crcs();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, png.chunks().len() - 1);
+        assert!(matches!(failures[0].1, ChunkError::InvalidCrc(_, _)));
+    }
+
+    #[test]
+    fn test_iter_with_offsets() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let bytes = png.as_bytes();
+        let mut count = 0;
+        for (offset, chunk) in png.iter_with_offsets() {
+            assert_eq!(&bytes[offset..offset + chunk.serialized_len()], chunk.as_bytes().as_slice());
+            count += 1;
+        }
+        assert_eq!(count, png.chunks().len());
+        assert_eq!(png.iter_with_offsets().next().unwrap().0, Png::STANDARD_HEADER.len());
+    }
+
+    #[test]
+    fn test_idat_data() {
+        assert!(testing_png().idat_data().is_empty());
+
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("IDAT", "first ").unwrap());
+        png.append_chunk(chunk_from_strings("teXt", "between").unwrap());
+        png.append_chunk(chunk_from_strings("IDAT", "second").unwrap());
+        assert_eq!(png.idat_data(), b"first second");
+    }
+
+    #[test]
+    fn test_chunk_types_str() {
+        assert_eq!(testing_png().chunk_types_str(), vec!["FrSt", "miDl", "LASt"]);
+        assert!(Png::new().chunk_types_str().is_empty());
+    }
+
+    #[test]
+    fn test_contains_type() {
+        let png = testing_png();
+        assert!(png.contains_type("miDl"));
+        assert!(!png.contains_type("TeSt"));
+        assert!(!png.contains_type("bad"));
+    }
+
+    #[test]
+    fn test_swap_chunks() {
+        let mut png = testing_png();
+        png.swap_chunks(0, 2).unwrap();
+        assert_eq!(png.chunk_types_str(), vec!["LASt", "miDl", "FrSt"]);
+        png.swap_chunks(1, 1).unwrap();
+        assert_eq!(png.chunk_types_str(), vec!["LASt", "miDl", "FrSt"]);
+
+        let err = png.swap_chunks(0, 3).unwrap_err();
+        assert!(matches!(err.downcast_ref::<PngError>(), Some(PngError::IndexOutOfBounds(3, 3))));
+    }
+
+    #[test]
+    fn test_move_chunk() {
+        let mut png = testing_png();
+        png.move_chunk(0, 2).unwrap();
+        assert_eq!(png.chunk_types_str(), vec!["miDl", "LASt", "FrSt"]);
+        png.move_chunk(2, 0).unwrap();
+        assert_eq!(png.chunk_types_str(), vec!["FrSt", "miDl", "LASt"]);
+
+        assert!(png.move_chunk(3, 0).is_err());
+        assert!(png.move_chunk(0, 3).is_err());
+        assert_eq!(png, testing_png());
+
+        let mut png = Png::new();
+        png.append_chunk(chunk_from_strings("IHDR", "").unwrap());
+        png.append_chunk(chunk_from_strings("IDAT", "").unwrap());
+        png.append_chunk(chunk_from_strings("gAMA", "").unwrap());
+        png.move_chunk(2, png.index_of_first_idat().unwrap()).unwrap();
+        assert!(png.validate_ordering().is_ok());
+    }
+
+    #[test]
+    fn test_structural_indices() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.index_of_ihdr(), Some(0));
+        assert_eq!(png.index_of_first_idat(), Some(4));
+        assert_eq!(png.index_of_iend(), Some(png.chunks().len() - 1));
+
+        let png = testing_png();
+        assert_eq!(png.index_of_ihdr(), None);
+        assert_eq!(png.index_of_first_idat(), None);
+        assert_eq!(png.index_of_iend(), None);
+
+        let mut png = Png::new();
+        png.append_chunk(chunk_from_strings("IHDR", "").unwrap());
+        png.append_chunk(chunk_from_strings("IDAT", "one").unwrap());
+        png.append_chunk(chunk_from_strings("IDAT", "two").unwrap());
+        assert_eq!(png.index_of_ihdr(), Some(0));
+        assert_eq!(png.index_of_first_idat(), Some(1));
+        assert_eq!(png.index_of_iend(), None);
+    }
+
+    #[test]
+    fn test_position_of_iend() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.position_of_iend(), Some(png.chunks().len() - 1));
+        assert_eq!(testing_png().position_of_iend(), None);
+    }
+
+    #[test]
+    fn test_starts_with_signature() {
+        assert!(Png::starts_with_signature(&PNG_FILE[..]).unwrap());
+        assert!(Png::starts_with_signature(&Png::STANDARD_HEADER[..]).unwrap());
+        assert!(!Png::starts_with_signature(&PNG_FILE[..7]).unwrap());
+        assert!(!Png::starts_with_signature(&b"GIF89a, not a png"[..]).unwrap());
+    }
+
+    #[test]
+    fn test_lenient_parse_trailing_bytes() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend_from_slice(b"trailing garbage");
+
+        assert!(Png::parse(&bytes, &ParseOptions::lenient()).is_ok());
+        assert!(Png::parse(&bytes, &ParseOptions::strict()).is_err());
+    }
+
+    #[test]
+    fn test_with_header() {
+        let header = *b"NOTAPNG!";
+        let png = testing_png().with_header(header);
+        assert_eq!(png.header(), &header);
+        assert!(png.as_bytes().starts_with(&header));
+
+        let mut written = vec![];
+        png.write_to(&mut written).unwrap();
+        assert_eq!(written, png.as_bytes());
+        assert_eq!(PngBuilder::from_png(png).finish().header(), &header);
+        assert_eq!(testing_png().header(), &Png::STANDARD_HEADER);
+    }
+
+    #[test]
+    fn test_lenient_parse_header() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes[0] = 13;
+
+        let png = Png::parse(&bytes, &ParseOptions::lenient()).unwrap();
+        assert_eq!(png.header()[0], 13);
+        assert_eq!(png.as_bytes(), bytes);
+        assert!(Png::parse(&bytes, &ParseOptions::strict()).is_err());
+    }
+
+    #[test]
+    fn test_strict_parse_validates() {
+        assert!(Png::parse(&PNG_FILE, &ParseOptions::strict()).is_ok());
+
+        let bytes = testing_png().as_bytes();
+        assert!(Png::parse(&bytes, &ParseOptions::lenient()).is_ok());
+        assert!(Png::parse(&bytes, &ParseOptions::strict()).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.validate().is_ok());
+
+        let png = testing_png();
+        assert!(png.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_iend_not_last() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.chunks.push(chunk_from_strings("TeSt", "Message").unwrap());
+        assert!(png.validate().is_err());
+    }
+
+    #[test]
+    fn test_builder_pushes_before_iend() {
+        let mut builder = PngBuilder::new();
+        builder.push_chunk(chunk_from_strings("IHDR", "").unwrap()).unwrap();
+        builder.push_chunk(chunk_from_strings("IEND", "").unwrap()).unwrap();
+        builder.push_chunk(chunk_from_strings("TeSt", "Message").unwrap()).unwrap();
+        let png = builder.finish();
+
+        let types: Vec<String> = png.chunks().iter().map(|c| c.chunk_type().to_string()).collect();
+        assert_eq!(types, vec!["IHDR", "TeSt", "IEND"]);
+    }
+
+    #[test]
+    fn test_builder_rejects_late_ihdr() {
+        let mut builder = PngBuilder::new();
+        builder.push_chunk(chunk_from_strings("TeSt", "Message").unwrap()).unwrap();
+        assert!(builder.push_chunk(chunk_from_strings("IHDR", "").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_builder_from_png_write_to() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut builder = PngBuilder::from_png(png);
+        builder.push_chunk(chunk_from_strings("TeSt", "Message").unwrap()).unwrap();
+
+        let mut bytes = Vec::new();
+        let written = builder.write_to(&mut bytes).unwrap();
+        assert_eq!(written, bytes.len());
+
+        let png = Png::try_from(bytes.as_ref()).unwrap();
+        let last_two: Vec<String> = png.chunks()[png.chunks().len() - 2..]
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect();
+        assert_eq!(last_two, vec!["TeSt", "IEND"]);
+    }
+
     #[test]
     fn test_png_from_image_file() {
         let png = Png::try_from(&PNG_FILE[..]);
         assert!(png.is_ok());
     }
 
+    #[test]
+    fn test_png_from_reader() {
+        let png = Png::from_reader(&PNG_FILE[..]).unwrap();
+        assert_eq!(png, Png::try_from(&PNG_FILE[..]).unwrap());
+
+        // every cut inside the first chunk after the header is a truncation
+        for cut in [10, 14, 20, 35] {
+            let err = Png::from_reader(&PNG_FILE[..cut]).unwrap_err();
+            assert!(
+                matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::UnexpectedEof { .. })),
+                "cut at {}: {:?}", cut, err
+            );
+        }
+        // a cut between chunks is not
+        assert_eq!(Png::from_reader(&PNG_FILE[..33]).unwrap().chunks().len(), 1);
+
+        let err = Png::from_reader(&PNG_FILE[..5]).unwrap_err();
+        assert!(matches!(err.downcast_ref::<PngError>(), Some(PngError::InputTooSmall)));
+        assert!(Png::from_reader(&b"GIF89a, not a png"[..]).is_err());
+    }
+
     #[test]
     fn test_as_bytes() {
         let png = Png::try_from(&PNG_FILE[..]).unwrap();
         let actual = png.as_bytes();
-        let expected: Vec<u8> = PNG_FILE.iter().copied().collect();
+        let expected: Vec<u8> = PNG_FILE.to_vec();
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_total_size() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.total_size(), PNG_FILE.len());
+        assert_eq!(Png::from_chunks(vec![]).total_size(), 8);
+    }
+
     #[test]
     fn test_png_trait_impls() {
         let chunk_bytes: Vec<u8> = testing_chunks()
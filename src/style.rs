@@ -0,0 +1,84 @@
+//! ANSI colors for terminal output. Colors are only ever turned on when the
+//! crate is built with the `color` feature and `--color` allows them.
+
+#[cfg(feature = "color")]
+use std::io::IsTerminal;
+
+#[cfg(feature = "color")]
+use clap::ValueEnum;
+
+use crate::args::GlobalArgs;
+
+/// When to color output, from `--color`.
+#[cfg(feature = "color")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+const CYAN: &str = "36";
+const GREEN: &str = "32";
+const RED: &str = "31";
+
+/// Wraps text in ANSI color codes, or passes it through unchanged when disabled.
+#[derive(Debug, Clone, Copy)]
+pub struct Style {
+    enabled: bool,
+}
+
+impl Style {
+    /// The style selected by the global arguments.
+    pub fn from_args(global: &GlobalArgs) -> Self {
+        #[cfg(feature = "color")]
+        let enabled = match global.color {
+            ColorChoice::Auto => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        #[cfg(not(feature = "color"))]
+        let enabled = {
+            let _ = global;
+            false
+        };
+        Self { enabled }
+    }
+
+    pub fn chunk_type(&self, text: &str) -> String {
+        self.paint(CYAN, text)
+    }
+
+    pub fn ok(&self, text: &str) -> String {
+        self.paint(GREEN, text)
+    }
+
+    pub fn error(&self, text: &str) -> String {
+        self.paint(RED, text)
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.enabled {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_style() {
+        let plain = Style { enabled: false };
+        assert_eq!(plain.error("bad crc"), "bad crc");
+
+        let colored = Style { enabled: true };
+        assert_eq!(colored.chunk_type("IHDR"), "\x1b[36mIHDR\x1b[0m");
+        assert_eq!(colored.ok("ok"), "\x1b[32mok\x1b[0m");
+    }
+}
@@ -0,0 +1,141 @@
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::Result;
+
+/// Character encodings a message can be stored in.
+/// `tEXt` chunks are defined as Latin-1, everything else in this crate uses utf-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Latin1,
+    #[cfg(feature = "encoding")]
+    Windows1252,
+}
+
+impl TextEncoding {
+    /// Converts `text` into the bytes stored in a chunk.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        match self {
+            TextEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+            TextEncoding::Latin1 => text
+                .chars()
+                .map(|c| u8::try_from(u32::from(c)).map_err(|_| TextEncodingError::Unmappable(c).into()))
+                .collect(),
+            #[cfg(feature = "encoding")]
+            TextEncoding::Windows1252 => {
+                let (bytes, _, had_unmappable) = encoding_rs::WINDOWS_1252.encode(text);
+                if had_unmappable {
+                    let c = text
+                        .chars()
+                        .find(|&c| encoding_rs::WINDOWS_1252.encode(&c.to_string()).2)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    return Err(Box::from(TextEncodingError::Unmappable(c)));
+                }
+                Ok(bytes.into_owned())
+            }
+        }
+    }
+
+    /// Converts the bytes stored in a chunk back into text.
+    pub fn decode(&self, bytes: &[u8]) -> Result<String> {
+        match self {
+            TextEncoding::Utf8 => Ok(std::str::from_utf8(bytes)?.to_string()),
+            TextEncoding::Latin1 => Ok(bytes.iter().map(|&b| char::from(b)).collect()),
+            #[cfg(feature = "encoding")]
+            TextEncoding::Windows1252 => {
+                let (text, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(bytes);
+                Ok(text.into_owned())
+            }
+        }
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = TextEncodingError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(TextEncoding::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(TextEncoding::Latin1),
+            #[cfg(feature = "encoding")]
+            "windows-1252" | "cp1252" => Ok(TextEncoding::Windows1252),
+            #[cfg(not(feature = "encoding"))]
+            "windows-1252" | "cp1252" => Err(TextEncodingError::FeatureDisabled(s.to_string())),
+            _ => Err(TextEncodingError::UnknownEncoding(s.to_string())),
+        }
+    }
+}
+
+// Text encoding errors
+#[derive(Debug)]
+pub enum TextEncodingError {
+    // The encoding name is not recognised
+    UnknownEncoding(String),
+
+    // The encoding is only available with the `encoding` feature
+    FeatureDisabled(String),
+
+    // The character cannot be represented in the target encoding
+    Unmappable(char),
+}
+
+impl std::error::Error for TextEncodingError {}
+
+impl Display for TextEncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextEncodingError::UnknownEncoding(name) => {
+                write!(f, "Unknown encoding: {}", name)
+            },
+            TextEncodingError::FeatureDisabled(name) => {
+                write!(f, "Encoding {} requires the `encoding` feature", name)
+            },
+            TextEncodingError::Unmappable(c) => {
+                write!(f, "Character {:?} cannot be represented in this encoding", c)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_round_trip() {
+        let bytes = TextEncoding::Utf8.encode("héllo").unwrap();
+        assert_eq!(bytes, "héllo".as_bytes());
+        assert_eq!(TextEncoding::Utf8.decode(&bytes).unwrap(), "héllo");
+    }
+
+    #[test]
+    fn test_latin1_round_trip() {
+        let bytes = TextEncoding::Latin1.encode("héllo").unwrap();
+        assert_eq!(bytes, vec![b'h', 0xE9, b'l', b'l', b'o']);
+        assert_eq!(TextEncoding::Latin1.decode(&bytes).unwrap(), "héllo");
+    }
+
+    #[test]
+    fn test_latin1_unmappable() {
+        assert!(TextEncoding::Latin1.encode("€").is_err());
+    }
+
+    #[test]
+    fn test_encoding_from_str() {
+        assert_eq!(TextEncoding::from_str("UTF-8").unwrap(), TextEncoding::Utf8);
+        assert_eq!(TextEncoding::from_str("latin1").unwrap(), TextEncoding::Latin1);
+        assert!(TextEncoding::from_str("ebcdic").is_err());
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn test_windows1252_round_trip() {
+        let bytes = TextEncoding::Windows1252.encode("€5").unwrap();
+        assert_eq!(bytes, vec![0x80, b'5']);
+        assert_eq!(TextEncoding::Windows1252.decode(&bytes).unwrap(), "€5");
+        assert!(TextEncoding::Windows1252.encode("日").is_err());
+    }
+}
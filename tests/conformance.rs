@@ -0,0 +1,154 @@
+// Conformance tests against small, spec-valid PNGs generated in code, plus
+// corrupt variants that must be rejected with the expected error.
+
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use pngme::chunk::{Chunk, ChunkError, ParseOptions};
+use pngme::chunk_type::ChunkType;
+use pngme::png::{Png, PngError};
+
+const GRAYSCALE: u8 = 0;
+const RGB: u8 = 2;
+const PALETTE: u8 = 3;
+
+fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+    Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+// A zlib stream holding `data` in a single uncompressed deflate block
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let len = data.len() as u16;
+    let mut stream = vec![0x78, 0x01, 0x01];
+    stream.extend_from_slice(&len.to_le_bytes());
+    stream.extend_from_slice(&(!len).to_le_bytes());
+    stream.extend_from_slice(data);
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+// A `width` x `height` 8 bit image, each scanline prefixed with filter type 0
+fn png_bytes(color_type: u8, width: u32, height: u32) -> Vec<u8> {
+    let samples_per_pixel = if color_type == RGB { 3 } else { 1 };
+
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, color_type, 0, 0, 0]);
+
+    let mut raw = Vec::new();
+    for y in 0..height {
+        raw.push(0);
+        for x in 0..width * samples_per_pixel {
+            raw.push(((x + y) % 2) as u8 * if color_type == PALETTE { 1 } else { 255 });
+        }
+    }
+
+    let mut chunks = vec![chunk("IHDR", ihdr)];
+    if color_type == PALETTE {
+        chunks.push(chunk("PLTE", vec![0, 0, 0, 255, 255, 255]));
+    }
+    chunks.push(chunk("tEXt", b"Comment\0conformance sample".to_vec()));
+    chunks.push(chunk("IDAT", zlib_stored(&raw)));
+    chunks.push(chunk("IEND", vec![]));
+
+    Png::from_chunks(chunks).as_bytes()
+}
+
+fn samples() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("grayscale", png_bytes(GRAYSCALE, 4, 4)),
+        ("rgb", png_bytes(RGB, 3, 2)),
+        ("palette", png_bytes(PALETTE, 5, 3)),
+    ]
+}
+
+#[test]
+fn test_iend_matches_spec_bytes() {
+    let iend = chunk("IEND", vec![]).as_bytes();
+    assert_eq!(iend, vec![0, 0, 0, 0, 73, 69, 78, 68, 0xAE, 0x42, 0x60, 0x82]);
+}
+
+#[test]
+fn test_samples_parse() {
+    for (name, bytes) in samples() {
+        assert!(Png::try_from(bytes.as_ref()).is_ok(), "{} failed to parse", name);
+    }
+}
+
+#[test]
+fn test_samples_round_trip() {
+    for (name, bytes) in samples() {
+        let png = Png::try_from(bytes.as_ref()).unwrap();
+        assert_eq!(png.as_bytes(), bytes, "{} did not round-trip", name);
+    }
+}
+
+// A sample with a critical chunk that isn't one of IHDR/PLTE/IDAT/IEND,
+// placed between IHDR and the image data
+fn unknown_critical_bytes() -> Vec<u8> {
+    let mut png = Png::try_from(png_bytes(RGB, 3, 2).as_ref()).unwrap();
+    let vendor = chunk("VENd", vec![0x00, 0xFF, 0x10, 0x80, b'x']);
+    assert!(vendor.chunk_type().is_critical());
+    png.insert_chunk(1, vendor).unwrap();
+    png.as_bytes()
+}
+
+#[test]
+fn test_unknown_critical_round_trip() {
+    let bytes = unknown_critical_bytes();
+
+    let png = Png::try_from(bytes.as_ref()).unwrap();
+    assert_eq!(png.chunks()[1].chunk_type().to_string(), "VENd");
+    assert_eq!(png.as_bytes(), bytes);
+
+    for options in [ParseOptions::strict(), ParseOptions::lenient()] {
+        let png = Png::parse(&bytes, &options).unwrap();
+        assert_eq!(png.as_bytes(), bytes);
+    }
+}
+
+#[test]
+fn test_samples_validate() {
+    for (name, bytes) in samples() {
+        let png = Png::try_from(bytes.as_ref()).unwrap();
+        assert!(png.validate().is_ok(), "{} failed validation", name);
+    }
+}
+
+#[test]
+fn test_bad_signature_rejected() {
+    let mut bytes = png_bytes(GRAYSCALE, 4, 4);
+    bytes[1] = b'J';
+
+    let err = Png::try_from(bytes.as_ref()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<PngError>(), Some(PngError::InvaldPngHeader)));
+}
+
+#[test]
+fn test_bad_crc_rejected() {
+    let mut bytes = png_bytes(RGB, 3, 2);
+    // last byte of the IHDR CRC: signature (8) + length (4) + type (4) + data (13) + crc (4)
+    bytes[8 + 4 + 4 + 13 + 3] ^= 0xFF;
+
+    let err = Png::try_from(bytes.as_ref()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InvalidCrc(_, _))));
+}
+
+#[test]
+fn test_truncated_rejected() {
+    let mut bytes = png_bytes(PALETTE, 5, 3);
+    bytes.truncate(bytes.len() - 20);
+
+    let err = Png::try_from(bytes.as_ref()).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ChunkError>(), Some(ChunkError::InputTooSmall)));
+}
@@ -0,0 +1,65 @@
+// Messages of every shape must come back out of a PNG byte for byte,
+// whether or not they are valid UTF-8.
+
+use std::str::FromStr;
+
+use pngme::chunk::Chunk;
+use pngme::chunk_type::ChunkType;
+use pngme::in_memory::{decode_in_memory, encode_in_memory};
+use pngme::png::Png;
+
+fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+    Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+}
+
+// A 1x1 grayscale PNG: IHDR, one IDAT and IEND
+fn minimal_png() -> Vec<u8> {
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&1u32.to_be_bytes());
+    ihdr.extend_from_slice(&1u32.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 0, 0, 0, 0]);
+    // zlib stream of the single scanline [filter 0, pixel 0]
+    let idat = vec![0x78, 0x01, 0x01, 0x02, 0x00, 0xfd, 0xff, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01];
+    Png::from_chunks(vec![chunk("IHDR", ihdr), chunk("IDAT", idat), chunk("IEND", vec![])]).as_bytes()
+}
+
+// Deterministic xorshift bytes, so failures reproduce
+fn pseudo_random(len: usize, mut state: u64) -> Vec<u8> {
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn assert_round_trip(name: &str, message: &[u8]) {
+    let png = minimal_png();
+    let encoded = encode_in_memory(&png, "ruSt", message).unwrap();
+    let decoded = decode_in_memory(&encoded, "ruSt").unwrap();
+    assert_eq!(decoded.as_deref(), Some(message), "{} did not round-trip", name);
+}
+
+#[test]
+fn test_round_trip_patterns() {
+    let cases: Vec<(&str, Vec<u8>)> = vec![
+        ("empty", vec![]),
+        ("all zeros", vec![0; 1024]),
+        ("all 0xFF", vec![0xFF; 1024]),
+        ("random", pseudo_random(4096, 0x9E37_79B9_7F4A_7C15)),
+        ("valid utf-8", "hidden message, verborgene Nachricht, 隠されたメッセージ 🦀".as_bytes().to_vec()),
+        ("invalid utf-8", vec![0xC3, 0x28, 0xA0, 0xA1, 0xE2, 0x28, 0xA1, 0xF0, 0x28, 0x8C, 0xBC, 0xFF, 0xFE]),
+        ("nul bytes", b"key\0value\0".to_vec()),
+    ];
+    for (name, message) in cases {
+        assert_round_trip(name, &message);
+    }
+}
+
+#[test]
+fn test_round_trip_large_payload() {
+    let message = pseudo_random(4 * 1024 * 1024, 42);
+    assert_round_trip("4 MiB random", &message);
+}